@@ -0,0 +1,200 @@
+use std::collections::HashSet;
+
+use super::idea::{Idea, Status};
+use super::outline::{Outline, filter_rows};
+use super::state::Handler as StateHandler;
+use super::utils::truncate;
+use super::{
+    App, Block, Borders, Color, Constraint, Direction, Frame, KeyCode, KeyEvent, Layout, Line,
+    Paragraph, Rect, Style,
+};
+
+/// The indices of the ideas in each status, in outline order. Sub-ideas are
+/// cards of their own, so collapsing doesn't hide them here.
+pub fn columns(ideas: &[Idea], show_archived: bool) -> Vec<Vec<usize>> {
+    let rows = Outline::new(ideas).rows(ideas, &HashSet::new());
+    let rows = filter_rows(rows, |i| show_archived || !ideas[i].archived);
+    Status::ALL
+        .iter()
+        .map(|status| {
+            rows.iter()
+                .map(|(index, _)| *index)
+                .filter(|index| ideas[*index].status == *status)
+                .collect()
+        })
+        .collect()
+}
+
+/// The ideas laid out in one column per status, as an alternative to the
+/// list. It shares the list's selection, so the usual keys keep working.
+pub trait Handler {
+    fn toggle_board(&mut self);
+    /// Handles the keys that mean something else on the board, returning
+    /// whether `key_event` was one of them.
+    fn handle_board_key(&mut self, key_event: KeyEvent) -> bool;
+    fn board_move(&mut self, rows: isize, columns: isize);
+    fn move_status(&mut self, columns: isize);
+    fn render_board(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl App {
+    /// The columns with only the ideas the filter lets through.
+    fn board_columns(&self) -> Vec<Vec<usize>> {
+        let mut board = columns(&self.ideas, self.show_archived);
+        if let Some(shown) = self.filtered() {
+            for column in board.iter_mut() {
+                column.retain(|i| shown.contains(i));
+            }
+        }
+        board
+    }
+
+    /// The column and row of the selected idea, if it's on the board.
+    fn board_position(&self, columns: &[Vec<usize>]) -> Option<(usize, usize)> {
+        columns.iter().enumerate().find_map(|(column, indices)| {
+            indices
+                .iter()
+                .position(|i| *i == self.active_index)
+                .map(|row| (column, row))
+        })
+    }
+}
+
+impl Handler for App {
+    fn toggle_board(&mut self) {
+        self.board = !self.board;
+        if self.board && self.board_position(&self.board_columns()).is_none() {
+            self.board_move(0, 0);
+        }
+    }
+
+    fn handle_board_key(&mut self, key_event: KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => self.board_move(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => self.board_move(1, 0),
+            KeyCode::Left | KeyCode::Char('h') => self.board_move(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => self.board_move(0, 1),
+            KeyCode::Char('H') => self.move_status(-1),
+            KeyCode::Char('L') => self.move_status(1),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Moves the selection by `rows` within its column or `columns` across,
+    /// skipping empty columns and keeping the row where it can.
+    fn board_move(&mut self, rows: isize, columns: isize) {
+        let board = self.board_columns();
+        let Some((column, row)) = self.board_position(&board) else {
+            if let Some(first) = board.iter().flatten().next() {
+                self.active_index = *first;
+            }
+            return;
+        };
+        if rows != 0 {
+            let row = row.saturating_add_signed(rows).min(board[column].len() - 1);
+            self.active_index = board[column][row];
+            return;
+        }
+        let mut target = column;
+        loop {
+            match target.checked_add_signed(columns) {
+                Some(next) if next < board.len() => target = next,
+                _ => return,
+            }
+            if let Some(last) = board[target].len().checked_sub(1) {
+                self.active_index = board[target][row.min(last)];
+                return;
+            }
+        }
+    }
+
+    /// Gives the selected idea the status of the column `columns` away.
+    fn move_status(&mut self, columns: isize) {
+        let Some(idea) = self.ideas.get_mut(self.active_index) else {
+            return;
+        };
+        let column = Status::ALL.iter().position(|s| *s == idea.status);
+        let Some(status) = column
+            .and_then(|c| c.checked_add_signed(columns))
+            .and_then(|c| Status::ALL.get(c))
+        else {
+            return;
+        };
+        idea.status = *status;
+        idea.touch();
+        let message = format!("Status: {}", status.label());
+        self.set_status(&message);
+        self.schedule_save();
+    }
+
+    fn render_board(&mut self, frame: &mut Frame, area: Rect) {
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+
+        let board = self.board_columns();
+        let areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 4); 4])
+            .split(area);
+        for ((status, indices), area) in Status::ALL.iter().zip(&board).zip(areas.iter()) {
+            let selected = indices.iter().position(|i| *i == self.active_index);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} {} ", status.label(), indices.len()))
+                .border_style(if selected.is_some() {
+                    active_style
+                } else {
+                    passive_style
+                });
+            let inner = block.inner(*area);
+            let width = inner.width.saturating_sub(2) as usize;
+            let lines: Vec<Line> = indices
+                .iter()
+                .map(|index| {
+                    let idea = &self.ideas[*index];
+                    let mark = match (self.selecting, self.selected.contains(&idea.id)) {
+                        (false, _) => "",
+                        (true, true) => "[x] ",
+                        (true, false) => "[ ] ",
+                    };
+                    let title = truncate(&idea.title, width.saturating_sub(mark.len()));
+                    if *index == self.active_index {
+                        Line::styled(format!("> {}{}", mark, title), active_style)
+                    } else {
+                        Line::styled(format!("  {}{}", mark, title), text_style)
+                    }
+                })
+                .collect();
+            let height = inner.height.max(1);
+            let offset = (selected.unwrap_or(0) as u16).saturating_sub(height - 1);
+            let para = Paragraph::new(lines).block(block).scroll((offset, 0));
+            frame.render_widget(para, *area);
+        }
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_board_columns() {
+        use crate::app::board::columns;
+        use crate::app::idea::{Idea, Status};
+
+        let parent = Idea::new("parent", "");
+        let mut child = Idea::new("child", "");
+        child.parent = Some(parent.id);
+        child.status = Status::Active;
+        let mut done = Idea::new("done", "");
+        done.status = Status::Done;
+        let mut archived = Idea::new("archived", "");
+        archived.archived = true;
+        let ideas = vec![child, parent, done, archived];
+
+        assert_eq!(
+            columns(&ideas, false),
+            vec![vec![1], vec![0], vec![], vec![2]]
+        );
+        assert_eq!(columns(&ideas, true)[0], vec![1, 3]);
+    }
+}
@@ -5,7 +5,7 @@ use super::{Block, Frame, Line, Paragraph};
 pub struct View {
     width: u16,
     buffer: String,
-    cursor_position: (u16, u16),
+    cursor: usize,
 }
 
 impl View {
@@ -13,7 +13,7 @@ impl View {
         Self {
             width: 0,
             buffer: String::new(),
-            cursor_position: (0, 0),
+            cursor: 0,
         }
     }
 
@@ -23,50 +23,164 @@ impl View {
 
     pub fn set_buffer(&mut self, buffer: &str) {
         self.buffer = buffer.to_string();
+        self.cursor = self.buffer.chars().count();
     }
 
-    pub fn build(&mut self) -> Vec<String> {
-        let mut vec: Vec<String> = Vec::new();
-        let mut buf_string: Vec<String> = Vec::new();
-        let words = self.buffer.split_whitespace();
-
-        for word in words {
-            let x = word.len() as u16 + buf_string.join(" ").len() as u16;
-            if x > self.width {
-                vec.push(buf_string.join(" ").to_string());
-                buf_string.clear();
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    pub fn insert(&mut self, char: char) {
+        let index = self.byte_index();
+        self.buffer.insert(index, char);
+        self.cursor += 1;
+    }
+
+    /// Inserts `text` at the cursor in one go, e.g. a paste.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let index = self.byte_index();
+        self.buffer.insert_str(index, &text);
+        self.cursor += text.chars().count();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let index = self.byte_index();
+            self.buffer.remove(index);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.buffer.chars().count() {
+            let index = self.byte_index();
+            self.buffer.remove(index);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.buffer.chars().count());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.buffer.chars().count();
+    }
+
+    fn byte_index(&self) -> usize {
+        self.buffer
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.buffer.len())
+    }
+
+    /// Wraps the buffer at word boundaries, returning the char range of every line.
+    /// Words longer than the width are split, newlines always start a new line.
+    fn wrap(&self) -> Vec<(usize, usize)> {
+        let chars: Vec<char> = self.buffer.chars().collect();
+        let width = self.width.max(1) as usize;
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+
+        while i < chars.len() {
+            if chars[i] == '\n' {
+                lines.push((start, i));
+                i += 1;
+                start = i;
+                continue;
             }
-            buf_string.push(word.to_string());
+
+            let mut word_end = i;
+            while word_end < chars.len() && !chars[word_end].is_whitespace() {
+                word_end += 1;
+            }
+            let mut token_end = word_end;
+            while token_end < chars.len()
+                && chars[token_end] != '\n'
+                && chars[token_end].is_whitespace()
+            {
+                token_end += 1;
+            }
+
+            if i > start && word_end - start > width {
+                lines.push((start, i));
+                start = i;
+            }
+            while word_end - start > width {
+                lines.push((start, start + width));
+                start += width;
+            }
+            i = token_end;
         }
-        vec.push(buf_string.join(" ").to_string());
+        lines.push((start, chars.len()));
 
-        vec
+        lines
+    }
+
+    pub fn build(&mut self) -> Vec<String> {
+        let chars: Vec<char> = self.buffer.chars().collect();
+        self.wrap()
+            .into_iter()
+            .map(|(start, end)| chars[start..end].iter().collect())
+            .collect()
     }
 
     pub fn render(&mut self, frame: &mut Frame, block: Block, area: Rect) {
         let lines = self.build();
         let text: Vec<Line> = lines.iter().map(|l| Line::from(l.as_str())).collect();
-        // let text = Line::from(lines.as_str());
-        let paragraph = Paragraph::new(text).block(block);
+        let scroll = self.scroll_offset(area.height.saturating_sub(2));
+        let paragraph = Paragraph::new(text).block(block).scroll((scroll, 0));
 
         frame.render_widget(paragraph, area);
     }
 
-    pub fn get_last_word_cursor_position(&mut self) -> (u16, u16) {
+    /// Position of the cursor inside the wrapped lines, relative to the top left
+    /// of the text.
+    pub fn cursor_position(&self) -> (u16, u16) {
         assert_ne!(self.width, 0);
-        let lines = self.build();
-        let x;
-        match lines.last() {
-            Some(line) => x = line.len() as u16,
-            None => x = 0,
+        let lines = self.wrap();
+        let mut y = 0;
+        for (i, (start, _)) in lines.iter().enumerate() {
+            if *start <= self.cursor {
+                y = i;
+            }
+        }
+        let x = self.cursor - lines[y].0;
+        if x >= self.width as usize {
+            return (0, y as u16 + 1);
         }
-        let y = (lines.len() as u16 - 1).max(0);
-        (x, y)
+        (x as u16, y as u16)
+    }
+
+    /// Moves the cursor to a position inside the wrapped lines, e.g. where the
+    /// text was clicked.
+    pub fn set_cursor_position(&mut self, x: u16, y: u16) {
+        let lines = self.wrap();
+        let (start, end) = lines[(y as usize).min(lines.len() - 1)];
+        self.cursor = (start + x as usize).min(end);
+    }
+
+    /// Number of lines to scroll so the cursor stays inside `height` lines.
+    pub fn scroll_offset(&self, height: u16) -> u16 {
+        self.cursor_position()
+            .1
+            .saturating_sub(height.saturating_sub(1))
     }
 }
 
 mod tests {
     #[test]
+    #[allow(clippy::vec_init_then_push)]
     fn test_get_view_buffer() {
         use crate::app::buffer::View;
 
@@ -80,7 +194,7 @@ mod tests {
         truth.push(String::from("Hello my "));
         truth.push(String::from("name is "));
         truth.push(String::from("Siddarth "));
-        truth.push(String::from("Saha "));
+        truth.push(String::from("Saha"));
 
         assert_eq!(res, truth);
 
@@ -92,7 +206,7 @@ mod tests {
         let mut truth = Vec::new();
 
         truth.push(String::from("Hello my name is Siddarth Saha and I love "));
-        truth.push(String::from("programming "));
+        truth.push(String::from("programming"));
         assert_eq!(res, truth);
     }
 
@@ -104,8 +218,8 @@ mod tests {
         view.set_width(10);
         view.set_buffer(buffer);
         view.build();
-        let res = view.get_last_word_cursor_position();
-        let truth = (5, 4);
+        let res = view.cursor_position();
+        let truth = (4, 3);
         assert_eq!(res, truth);
 
         let buffer = "Hello my name is Siddarth Saha. I love programming";
@@ -114,8 +228,35 @@ mod tests {
         view.set_buffer(buffer);
         view.build();
 
-        let res = view.get_last_word_cursor_position();
-        let truth = (0, 5);
+        let res = view.cursor_position();
+        let truth = (1, 6);
         assert_eq!(res, truth);
+
+        view.move_home();
+        assert_eq!(view.cursor_position(), (0, 0));
+        for _ in 0..9 {
+            view.move_right();
+        }
+        assert_eq!(view.cursor_position(), (0, 1));
+    }
+
+    #[test]
+    fn test_edit_at_cursor() {
+        use crate::app::buffer::View;
+        let mut view = View::new();
+        view.set_width(20);
+        view.set_buffer("Helo");
+        view.move_left();
+        view.insert('l');
+        assert_eq!(view.text(), "Hello");
+        view.move_end();
+        view.backspace();
+        view.move_home();
+        view.delete();
+        assert_eq!(view.text(), "ell");
+        view.move_right();
+        view.insert_str("a\r\nb");
+        assert_eq!(view.text(), "ea\nbll");
+        assert_eq!(view.cursor_position(), (1, 1));
     }
 }
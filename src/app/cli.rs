@@ -0,0 +1,459 @@
+use std::collections::HashSet;
+use std::io::{BufRead, Error, ErrorKind, IsTerminal, Write};
+use std::path::Path;
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+
+use super::config::Config;
+use super::crypto::Cipher;
+use super::dedupe::duplicate_pairs;
+use super::git::{ENCRYPTED, Git};
+use super::idea::Idea;
+use super::idea::now;
+use super::merge::{Conflict, display_value, merge};
+use super::outline::{Outline, filter_rows};
+use super::query::Query;
+use super::search::{SearchIndex, index_path};
+use super::stats::Stats;
+use super::store::{Store, read_ideas};
+use super::timeline::{self, Group};
+use super::utils::{data_path, today};
+use super::views::load_views;
+
+const USAGE: &str = "usage: ideas [command]
+
+Without a command the TUI is opened.
+
+commands:
+  sync      commit the store, merge the remote branch and push (git mode)
+  merge BASE OURS THEIRS [--ours|--theirs]
+            merge two diverged copies of ideas.json that started from BASE
+            and write the result to OURS. Conflicts are asked about on a
+            terminal, or settled with --ours/--theirs. Exits with an error
+            while conflicts are left, so it works as a git merge driver
+  list [--query QUERY] [--view NAME] [--archived]
+            print the ideas as an outline, only those matching the query
+            or saved view when given, e.g. --query 'tag:rust created:<30d'
+  search WORDS [--limit N]
+            full-text search of titles, descriptions, checklists and tags,
+            best matches first (20 unless --limit says otherwise)
+  due [--week]
+            list ideas that are overdue or due today, including review
+            dates; with --week also the coming week. Prints nothing when
+            nothing is due, so it fits a shell login script
+  stats [--json]
+            counts per status and tag, ideas captured per week, median
+            age of open ideas and completion rate
+  dedupe    list pairs of ideas that look like the same one
+  encrypt   encrypt the store with a passphrase, or change its passphrase
+  decrypt   store the ideas as plain json again
+  help      show this message";
+
+/// Runs a subcommand without opening the TUI.
+pub fn run(args: &[String]) -> std::io::Result<()> {
+    let config = Config::load(&data_path("ideas.config.json"))?;
+    match args[0].as_str() {
+        "sync" => sync(&config),
+        "merge" => merge_files(&args[1..]),
+        "list" => list(&args[1..]),
+        "search" => search(&args[1..]),
+        "due" => due(&args[1..]),
+        "stats" => stats(&args[1..]),
+        "dedupe" => dedupe(),
+        "encrypt" => encrypt(&config),
+        "decrypt" => decrypt(),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        command => Err(Error::other(format!(
+            "unknown command {:?}\n\n{}",
+            command, USAGE
+        ))),
+    }
+}
+
+fn sync(config: &Config) -> std::io::Result<()> {
+    let store = Store::new(data_path("ideas.json"));
+    let git = Git::new(store.path());
+    git.init()?;
+
+    // Hold the store lock so a running TUI doesn't write mid-merge; it picks
+    // up the result through its reload.
+    let _lock = store.lock(true)?;
+    if git.commit("sync: local changes")? {
+        println!("committed local changes");
+    }
+
+    let remote = config.git.remote.as_deref().unwrap_or("origin");
+    let report = git.sync(remote, config.git.branch.as_deref())?;
+    if report.pulled {
+        println!("pulled changes from {}", remote);
+    }
+    for conflict in &report.conflicts {
+        println!("conflict: kept local version of {:?}", conflict.title());
+    }
+    if report.pushed {
+        println!("pushed to {}", remote);
+    }
+    if !report.pulled && !report.pushed {
+        println!("already up to date");
+    }
+    Ok(())
+}
+
+fn merge_files(args: &[String]) -> std::io::Result<()> {
+    let mut paths = Vec::new();
+    let mut side = None;
+    for arg in args {
+        match arg.as_str() {
+            "--ours" => side = Some(false),
+            "--theirs" => side = Some(true),
+            path => paths.push(Path::new(path)),
+        }
+    }
+    let [base, ours, theirs] = paths[..] else {
+        return Err(Error::other(format!(
+            "merge takes three files\n\n{}",
+            USAGE
+        )));
+    };
+
+    let (mut merged, conflicts) =
+        merge(&read_ideas(base)?, &read_ideas(ours)?, &read_ideas(theirs)?);
+    let interactive = side.is_none() && std::io::stdin().is_terminal();
+    let mut unresolved = 0;
+    for conflict in &conflicts {
+        let choice = match side {
+            Some(true) => Some(all_fields(conflict)),
+            Some(false) => Some(Vec::new()),
+            None if interactive => Some(ask(conflict)?),
+            None => None,
+        };
+        let Some(theirs) = choice else {
+            unresolved += 1;
+            println!("conflict: kept our version of {:?}", conflict.title());
+            continue;
+        };
+        let position = merged.iter().position(|i| i.id == conflict.id);
+        match (conflict.resolve(&theirs), position) {
+            (Some(idea), Some(index)) => merged[index] = idea,
+            (Some(idea), None) => merged.push(idea),
+            (None, Some(index)) => {
+                merged.remove(index);
+            }
+            (None, None) => {}
+        }
+    }
+
+    std::fs::write(ours, serde_json::to_string_pretty(&merged)?)?;
+    if unresolved > 0 {
+        return Err(Error::other(format!(
+            "{} conflicts left, rerun with --ours or --theirs to settle them",
+            unresolved
+        )));
+    }
+    Ok(())
+}
+
+/// Indices that take every conflicting field from their side. A delete
+/// conflict has no fields and uses index 0 for their side.
+fn all_fields(conflict: &Conflict) -> Vec<usize> {
+    (0..conflict.fields.len().max(1)).collect()
+}
+
+/// Asks on the terminal which side to take for each conflicting field.
+fn ask(conflict: &Conflict) -> std::io::Result<Vec<usize>> {
+    println!("conflict in {:?}", conflict.title());
+    let questions: Vec<(String, String, String)> = if conflict.fields.is_empty() {
+        let describe = |idea: &Option<_>| match idea {
+            Some(_) => String::from("edited"),
+            None => String::from("deleted"),
+        };
+        vec![(
+            String::from("idea"),
+            describe(&conflict.ours),
+            describe(&conflict.theirs),
+        )]
+    } else {
+        conflict
+            .fields
+            .iter()
+            .map(|f| {
+                (
+                    f.name.clone(),
+                    display_value(&f.ours),
+                    display_value(&f.theirs),
+                )
+            })
+            .collect()
+    };
+
+    let mut theirs = Vec::new();
+    let mut lines = std::io::stdin().lock().lines();
+    for (index, (name, our, their)) in questions.iter().enumerate() {
+        println!("  {}\n    ours:   {}\n    theirs: {}", name, our, their);
+        loop {
+            print!("  keep [o]urs or [t]heirs? ");
+            std::io::stdout().flush()?;
+            match lines.next().transpose()?.as_deref().map(str::trim) {
+                Some("o") | Some("ours") => break,
+                Some("t") | Some("theirs") => {
+                    theirs.push(index);
+                    break;
+                }
+                Some(_) => continue,
+                None => return Err(Error::other("merge aborted")),
+            }
+        }
+    }
+    Ok(theirs)
+}
+
+/// Reads the store, asking for the passphrase when it's encrypted.
+fn load_ideas() -> std::io::Result<Vec<Idea>> {
+    load_store().map(|(_, ideas)| ideas)
+}
+
+fn load_store() -> std::io::Result<(Store, Vec<Idea>)> {
+    let mut store = Store::new(data_path("ideas.json"));
+    let ideas = match store.load() {
+        Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+            store.set_cipher(Some(Cipher::new(&read_passphrase("passphrase: ")?)));
+            store.load()?
+        }
+        result => result?,
+    };
+    Ok((store, ideas))
+}
+
+fn list(args: &[String]) -> std::io::Result<()> {
+    let mut query = None;
+    let mut archived = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--query" | "-q" => query = args.next().cloned(),
+            "--view" => {
+                let name = args.next().map(String::as_str).unwrap_or_default();
+                let views = load_views(&data_path("views.json"))?;
+                let view = views.into_iter().find(|v| v.name == name);
+                let Some(view) = view else {
+                    return Err(Error::other(format!("no view named {:?}", name)));
+                };
+                query = Some(view.query);
+            }
+            "--archived" => archived = true,
+            arg => {
+                return Err(Error::other(format!(
+                    "unknown option {:?}\n\n{}",
+                    arg, USAGE
+                )));
+            }
+        }
+    }
+    let query = match query {
+        Some(text) => Some(Query::parse(&text).map_err(Error::other)?),
+        None => None,
+    };
+
+    let ideas = load_ideas()?;
+    let today = today();
+    let rows = Outline::new(&ideas).rows(&ideas, &HashSet::new());
+    let rows = filter_rows(rows, |i| archived || !ideas[i].archived);
+    for (index, depth) in rows {
+        let idea = &ideas[index];
+        if query.as_ref().is_some_and(|q| !q.matches(idea, today)) {
+            continue;
+        }
+        let tags: String = idea.tags.iter().map(|t| format!(" #{}", t)).collect();
+        println!(
+            "{:<8} {}{}{}",
+            idea.status.label(),
+            "  ".repeat(depth),
+            idea.title,
+            tags
+        );
+    }
+    Ok(())
+}
+
+fn search(args: &[String]) -> std::io::Result<()> {
+    let mut words = Vec::new();
+    let mut limit = 20;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--limit" | "-n" => {
+                let value = args.next().map(String::as_str).unwrap_or_default();
+                limit = value
+                    .parse()
+                    .map_err(|_| Error::other(format!("can't read {:?} as a number", value)))?;
+            }
+            word => words.push(word),
+        }
+    }
+    if words.is_empty() {
+        return Err(Error::other(format!("search needs words\n\n{}", USAGE)));
+    }
+
+    let (store, ideas) = load_store()?;
+    // An encrypted store keeps no index on disk, it would give its words away.
+    let path = index_path(store.path());
+    let mut index = if store.is_encrypted() {
+        SearchIndex::default()
+    } else {
+        SearchIndex::load(&path)
+    };
+    if index.sync(&ideas) && !store.is_encrypted() {
+        index.save(&path)?;
+    }
+    for (id, score) in index.search(&words.join(" ")).into_iter().take(limit) {
+        if let Some(idea) = ideas.iter().find(|i| i.id == id) {
+            println!("{:>6.2}  {}", score, idea.title);
+        }
+    }
+    Ok(())
+}
+
+fn due(args: &[String]) -> std::io::Result<()> {
+    let week = args.iter().any(|a| a == "--week");
+    let ideas = load_ideas()?;
+    let mut group = None;
+    for entry in timeline::entries(&ideas, today()) {
+        let shown = entry.group.is_due() || (week && entry.group == Group::ThisWeek);
+        if !shown {
+            continue;
+        }
+        if group != Some(entry.group) {
+            println!("{}", entry.group.label().to_lowercase());
+            group = Some(entry.group);
+        }
+        println!(
+            "  {}  {:<7} {}",
+            entry.date, entry.kind, ideas[entry.index].title
+        );
+    }
+    Ok(())
+}
+
+fn stats(args: &[String]) -> std::io::Result<()> {
+    let stats = Stats::new(&load_ideas()?, now(), today());
+    if args.iter().any(|a| a == "--json") {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    for line in stats.summary() {
+        println!("{}", line);
+    }
+    println!("captured per week");
+    for week in &stats.weeks {
+        println!("  {}  {}", week.start, week.count);
+    }
+    Ok(())
+}
+
+fn dedupe() -> std::io::Result<()> {
+    let ideas = load_ideas()?;
+    for (a, b, score) in duplicate_pairs(&ideas) {
+        println!(
+            "{:>3.0}%  {}\n      {}",
+            score * 100.0,
+            ideas[a].title,
+            ideas[b].title
+        );
+    }
+    Ok(())
+}
+
+fn encrypt(config: &Config) -> std::io::Result<()> {
+    if config.git.enabled {
+        return Err(Error::other(ENCRYPTED));
+    }
+    let mut store = Store::new(data_path("ideas.json"));
+    let ideas = match store.load() {
+        Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+            store.set_cipher(Some(Cipher::new(&read_passphrase("current passphrase: ")?)));
+            store.load()?
+        }
+        result => result?,
+    };
+    let rekey = store.is_encrypted();
+
+    let passphrase = read_passphrase("new passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err(Error::other("the passphrase can't be empty"));
+    }
+    if read_passphrase("repeat passphrase: ")? != passphrase {
+        return Err(Error::other("the passphrases don't match"));
+    }
+    store.set_cipher(Some(Cipher::new(&passphrase)));
+    store.save(&ideas)?;
+    match std::fs::remove_file(index_path(store.path())) {
+        Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    if rekey {
+        println!("changed the passphrase of {}", store.path().display());
+    } else {
+        println!("encrypted {}", store.path().display());
+    }
+    Ok(())
+}
+
+fn decrypt() -> std::io::Result<()> {
+    let mut store = Store::new(data_path("ideas.json"));
+    match store.load() {
+        Ok(_) => return Err(Error::other("the store isn't encrypted")),
+        Err(err) if err.kind() == ErrorKind::PermissionDenied => {}
+        Err(err) => return Err(err),
+    }
+    store.set_cipher(Some(Cipher::new(&read_passphrase("passphrase: ")?)));
+    let ideas = store.load()?;
+    store.set_cipher(None);
+    store.save(&ideas)?;
+    println!("decrypted {}", store.path().display());
+    Ok(())
+}
+
+/// Reads a passphrase without echoing it. Off a terminal, e.g. in scripts,
+/// it is read as a line from stdin.
+fn read_passphrase(prompt: &str) -> std::io::Result<String> {
+    if !std::io::stdin().is_terminal() {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        return Ok(line.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    eprint!("{}", prompt);
+    std::io::stderr().flush()?;
+    crossterm::terminal::enable_raw_mode()?;
+    let result = read_hidden_line();
+    crossterm::terminal::disable_raw_mode()?;
+    eprintln!();
+    result
+}
+
+fn read_hidden_line() -> std::io::Result<String> {
+    let mut line = String::new();
+    loop {
+        let Event::Key(key) = crossterm::event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => return Ok(line),
+            KeyCode::Esc => return Err(Error::other("aborted")),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Err(Error::other("aborted"));
+            }
+            KeyCode::Char(char) => line.push(char),
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::io::Write;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use super::config::FieldConfig;
+use super::idea::Idea;
+use super::merge::display_value;
+use super::modal::{Action, Handler as ModalHandler, Modal};
+use super::state::Handler as StateHandler;
+use super::{App, Mode};
+
+/// What can be copied from the list, in the order the copy modal offers it.
+const CHOICES: [&str; 3] = ["Title", "Description", "Whole idea as Markdown"];
+
+/// The escape sequence that asks the terminal to put `text` on the system
+/// clipboard. Inside tmux it's wrapped so tmux passes it on.
+pub fn osc52(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if tmux {
+        format!("\x1bPtmux;\x1b{}\x1b\\", sequence)
+    } else {
+        sequence
+    }
+}
+
+/// An idea as a Markdown section, for pasting into issue trackers.
+pub fn markdown(idea: &Idea, fields: &[FieldConfig]) -> String {
+    let mut text = format!("# {}\n", idea.title);
+    if !idea.description.trim().is_empty() {
+        text.push_str(&format!("\n{}\n", idea.description.trim_end()));
+    }
+    if !idea.checklist.is_empty() {
+        text.push('\n');
+        for item in &idea.checklist {
+            let mark = if item.done { 'x' } else { ' ' };
+            text.push_str(&format!("- [{}] {}\n", mark, item.text));
+        }
+    }
+
+    let mut details = vec![format!("status: {}", idea.status.label())];
+    if !idea.tags.is_empty() {
+        let tags: Vec<String> = idea.tags.iter().map(|t| format!("#{}", t)).collect();
+        details.push(format!("tags: {}", tags.join(" ")));
+    }
+    if let Some(due) = idea.due {
+        details.push(format!("due: {}", due));
+    }
+    if let Some(review) = idea.review {
+        details.push(format!("review: {}", review));
+    }
+    for (name, value) in &idea.extra {
+        let label = fields
+            .iter()
+            .find(|f| f.name == *name)
+            .map_or(name.as_str(), |f| f.label());
+        details.push(format!("{}: {}", label, display_value(value)));
+    }
+    text.push('\n');
+    for line in details {
+        text.push_str(&format!("- {}\n", line));
+    }
+    text
+}
+
+/// Copying ideas to the system clipboard and pasting text into inputs.
+pub trait Handler {
+    fn open_copy_menu(&mut self);
+    /// Copies part of the selected idea, `choice` indexing `CHOICES`.
+    fn copy_active(&mut self, choice: usize);
+    /// Text pasted in the terminal, which arrives in one piece.
+    fn paste(&mut self, text: &str);
+}
+
+impl App {
+    /// Puts `text` on the system clipboard, and keeps it for Ctrl-V in the
+    /// idea popup in case the terminal ignores the request.
+    fn copy(&mut self, text: String) -> std::io::Result<()> {
+        let tmux = std::env::var_os("TMUX").is_some();
+        let mut stdout = std::io::stdout();
+        stdout.write_all(osc52(&text, tmux).as_bytes())?;
+        stdout.flush()?;
+        self.clipboard = Some(text);
+        Ok(())
+    }
+}
+
+impl Handler for App {
+    fn open_copy_menu(&mut self) {
+        if self.ideas.get(self.active_index).is_none() {
+            return;
+        }
+        self.open_modal(Modal::select(
+            "Copy",
+            "Copy to the clipboard",
+            CHOICES.iter().map(|c| c.to_string()).collect(),
+            Action::Copy,
+        ));
+    }
+
+    fn copy_active(&mut self, choice: usize) {
+        let Some(idea) = self.ideas.get(self.active_index) else {
+            return;
+        };
+        let text = match choice {
+            0 => idea.title.clone(),
+            1 => idea.description.clone(),
+            _ => markdown(idea, &self.config.fields),
+        };
+        match self.copy(text) {
+            Ok(()) => self.set_status(&format!("Copied {}", CHOICES[choice].to_lowercase())),
+            Err(err) => self.set_status(&format!("Couldn't copy: {}", err)),
+        }
+    }
+
+    fn paste(&mut self, text: &str) {
+        if let Some(modal) = self.modals.last_mut() {
+            modal.paste(text);
+            return;
+        }
+        // The one-line inputs take the first line.
+        let line = text.lines().next().unwrap_or_default();
+        match self.mode {
+            Mode::Form => {
+                if let Some(form) = self.form.as_mut() {
+                    form.paste(text);
+                }
+            }
+            Mode::Palette => {
+                self.palette_input.push_str(line);
+                self.palette_index = 0;
+            }
+            Mode::Search => {
+                self.search_input.push_str(line);
+                self.search_index = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_copy_formats() {
+        use crate::app::clipboard::{markdown, osc52};
+        use crate::app::idea::{ChecklistItem, Idea};
+
+        assert_eq!(osc52("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(osc52("hi", true), "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+
+        let mut idea = Idea::new("Socket server", "Async rust\n");
+        idea.tags = vec!["rust".into()];
+        idea.checklist.push(ChecklistItem {
+            text: "write tests".into(),
+            done: true,
+        });
+        idea.extra.insert("priority".into(), 2.into());
+        assert_eq!(
+            markdown(&idea, &[]),
+            "# Socket server\n\nAsync rust\n\n- [x] write tests\n\n- status: inbox\n- tags: #rust\n- priority: 2\n"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Settings read from `ideas.config.json` next to the store. Every field is
+/// optional and a missing file means defaults.
+#[derive(serde::Deserialize, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub review: ReviewConfig,
+    /// Extra fields ideas in this store have, shown in the idea popup.
+    #[serde(default)]
+    pub fields: Vec<FieldConfig>,
+}
+
+#[derive(serde::Deserialize, Default)]
+pub struct GitConfig {
+    /// Commit the store on every save.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Remote used by `ideas sync`, `origin` when unset.
+    pub remote: Option<String>,
+    /// Branch used by `ideas sync`, the checked out one when unset.
+    pub branch: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReviewConfig {
+    /// Ideas untouched for this many days come up in the review.
+    #[serde(default = "default_stale_days")]
+    pub stale_days: u64,
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            stale_days: default_stale_days(),
+        }
+    }
+}
+
+fn default_stale_days() -> u64 {
+    30
+}
+
+/// A user-defined field, kept in `Idea::extra` under its name.
+#[derive(serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldConfig {
+    pub name: String,
+    /// Shown in the popup instead of the name.
+    pub label: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: FieldKind,
+    /// The allowed values of an enum field.
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+impl FieldConfig {
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    #[default]
+    String,
+    Number,
+    Enum,
+    Date,
+    Url,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{Error, ErrorKind};
+
+use argon2::Argon2;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+const VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// How an encrypted store is laid out on disk: json, so the file still looks
+/// like a store to tools and git, with the ideas sealed inside `data`.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Envelope {
+    encrypted: u32,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+/// Encrypts and decrypts the store with a key derived from a passphrase
+/// (argon2id) using XChaCha20-Poly1305, so tampering is detected as well.
+pub struct Cipher {
+    passphrase: String,
+    salt: Vec<u8>,
+    key: Key,
+}
+
+impl Cipher {
+    /// The key is derived on first use, from the salt of the file being opened
+    /// or a fresh one when encrypting a store for the first time.
+    pub fn new(passphrase: &str) -> Self {
+        Self {
+            passphrase: passphrase.to_string(),
+            salt: Vec::new(),
+            key: Key::default(),
+        }
+    }
+
+    fn derive(&mut self, salt: Vec<u8>) -> std::io::Result<()> {
+        Argon2::default()
+            .hash_password_into(self.passphrase.as_bytes(), &salt, &mut self.key)
+            .map_err(|err| Error::other(format!("couldn't derive key: {}", err)))?;
+        self.salt = salt;
+        Ok(())
+    }
+
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> std::io::Result<Vec<u8>> {
+        if self.salt.is_empty() {
+            let mut salt = vec![0; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            self.derive(salt)?;
+        }
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let data = XChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| Error::other("couldn't encrypt the store"))?;
+        let envelope = Envelope {
+            encrypted: VERSION,
+            kdf: String::from("argon2id"),
+            salt: STANDARD.encode(&self.salt),
+            nonce: STANDARD.encode(nonce),
+            data: STANDARD.encode(data),
+        };
+        Ok(serde_json::to_vec_pretty(&envelope)?)
+    }
+
+    /// Opens an encrypted file, deriving the key again when the file was sealed
+    /// with another salt, e.g. re-encrypted elsewhere.
+    pub fn decrypt(&mut self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        let envelope: Envelope = serde_json::from_slice(bytes)?;
+        if envelope.encrypted != VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported store version {}", envelope.encrypted),
+            ));
+        }
+        let decode = |text: &str| {
+            STANDARD
+                .decode(text)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+        };
+        let salt = decode(&envelope.salt)?;
+        if salt != self.salt {
+            self.derive(salt)?;
+        }
+        let nonce = decode(&envelope.nonce)?;
+        if nonce.len() != 24 {
+            return Err(Error::new(ErrorKind::InvalidData, "bad nonce"));
+        }
+        XChaCha20Poly1305::new(&self.key)
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                decode(&envelope.data)?.as_slice(),
+            )
+            .map_err(|_| Error::new(ErrorKind::PermissionDenied, "wrong passphrase"))
+    }
+}
+
+/// Whether `bytes` hold an encrypted store rather than a plain idea list.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    serde_json::from_slice::<Envelope>(bytes).is_ok()
+}
+
+mod tests {
+    #[test]
+    fn test_round_trip() {
+        use crate::app::crypto::{Cipher, is_encrypted};
+        use std::io::ErrorKind;
+
+        let mut cipher = Cipher::new("correct horse");
+        let sealed = cipher.encrypt(b"[]").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!is_encrypted(b"[]"));
+
+        let mut other = Cipher::new("correct horse");
+        assert_eq!(other.decrypt(&sealed).unwrap(), b"[]");
+
+        let mut wrong = Cipher::new("battery staple");
+        let err = wrong.decrypt(&sealed).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
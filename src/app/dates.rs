@@ -0,0 +1,121 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+/// Reads a date the way people type it, relative to `today`: `today`,
+/// `tomorrow`, `friday` or `next friday` (the coming one), `next week`
+/// (its monday), `next month` (its first day), `in 3 days`, `2w`, `in 1m`,
+/// or an ISO date like `2025-06-30`.
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let text = input.trim().to_lowercase();
+    let text = text.strip_prefix("on ").unwrap_or(&text);
+    let invalid = || format!("can't read {:?} as a date", input.trim());
+
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    match text {
+        "today" | "now" => return Ok(today),
+        "tomorrow" => return today.checked_add_days(Days::new(1)).ok_or_else(invalid),
+        "yesterday" => return today.checked_sub_days(Days::new(1)).ok_or_else(invalid),
+        "next week" => return Ok(coming(today, Weekday::Mon)),
+        "next month" => {
+            return today
+                .with_day(1)
+                .and_then(|d| d.checked_add_months(Months::new(1)))
+                .ok_or_else(invalid);
+        }
+        _ => {}
+    }
+
+    let day = text.strip_prefix("next ").unwrap_or(text);
+    if let Some(weekday) = weekday(day) {
+        return Ok(coming(today, weekday));
+    }
+
+    let (count, unit) = parse_span(text.strip_prefix("in ").unwrap_or(text)).ok_or_else(invalid)?;
+    shift(today, count, unit, true).ok_or_else(invalid)
+}
+
+/// An amount of time like `3d`, `2 weeks` or `1y`, as the count and the
+/// unit's letter: `d`, `w`, `m` or `y`.
+pub fn parse_span(text: &str) -> Option<(u32, char)> {
+    let text = text.trim().to_lowercase().replace(' ', "");
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let count = text[..split].parse().ok()?;
+    let unit = match &text[split..] {
+        "d" | "day" | "days" => 'd',
+        "w" | "week" | "weeks" => 'w',
+        "m" | "month" | "months" => 'm',
+        "y" | "year" | "years" => 'y',
+        _ => return None,
+    };
+    Some((count, unit))
+}
+
+/// `today` moved `count` units ahead, or back when `ahead` is false.
+pub fn shift(today: NaiveDate, count: u32, unit: char, ahead: bool) -> Option<NaiveDate> {
+    let days = |days: u64| {
+        if ahead {
+            today.checked_add_days(Days::new(days))
+        } else {
+            today.checked_sub_days(Days::new(days))
+        }
+    };
+    let months = |months: u32| {
+        if ahead {
+            today.checked_add_months(Months::new(months))
+        } else {
+            today.checked_sub_months(Months::new(months))
+        }
+    };
+    match unit {
+        'd' => days(count as u64),
+        'w' => days(count as u64 * 7),
+        'm' => months(count),
+        _ => months(count.checked_mul(12)?),
+    }
+}
+
+fn weekday(name: &str) -> Option<Weekday> {
+    let weekday = match name {
+        "monday" | "mon" => Weekday::Mon,
+        "tuesday" | "tue" | "tues" => Weekday::Tue,
+        "wednesday" | "wed" => Weekday::Wed,
+        "thursday" | "thu" | "thurs" => Weekday::Thu,
+        "friday" | "fri" => Weekday::Fri,
+        "saturday" | "sat" => Weekday::Sat,
+        "sunday" | "sun" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+/// The first `weekday` after `today`.
+fn coming(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Days::new(if ahead == 0 { 7 } else { ahead as u64 })
+}
+
+mod tests {
+    #[test]
+    fn test_parse_date() {
+        use crate::app::dates::parse_date;
+        use chrono::NaiveDate;
+
+        // A wednesday.
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let date = |y, m, d| Ok(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+
+        assert_eq!(parse_date("today", today), date(2025, 1, 15));
+        assert_eq!(parse_date("Tomorrow", today), date(2025, 1, 16));
+        assert_eq!(parse_date("next friday", today), date(2025, 1, 17));
+        assert_eq!(parse_date("wed", today), date(2025, 1, 22));
+        assert_eq!(parse_date("next week", today), date(2025, 1, 20));
+        assert_eq!(parse_date("next month", today), date(2025, 2, 1));
+        assert_eq!(parse_date("in 2w", today), date(2025, 1, 29));
+        assert_eq!(parse_date("in 3 days", today), date(2025, 1, 18));
+        assert_eq!(parse_date("1m", today), date(2025, 2, 15));
+        assert_eq!(parse_date("2025-06-30", today), date(2025, 6, 30));
+        assert!(parse_date("someday soon", today).is_err());
+        assert!(parse_date("400000000y", today).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use super::idea::Idea;
+use super::modal::{Action, Handler as ModalHandler, Modal};
+use super::outline::Handler as OutlineHandler;
+use super::state::Handler as StateHandler;
+use super::{App, Mode};
+
+/// Ideas at least this similar are taken for the same one.
+pub const THRESHOLD: f64 = 0.8;
+
+/// What to do with a new idea that looks like an existing one, in the order
+/// offered.
+const CHOICES: [&str; 3] = ["Open the existing idea", "Merge into it", "Add anyway"];
+
+/// Lowercase words of `text` joined by single spaces, without punctuation.
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Pairs of neighbouring characters with how often each occurs.
+fn bigrams(text: &str) -> HashMap<(char, char), usize> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut bigrams = HashMap::new();
+    for pair in chars.windows(2) {
+        *bigrams.entry((pair[0], pair[1])).or_default() += 1;
+    }
+    bigrams
+}
+
+/// A text reduced to what similarity compares, worked out once so a text
+/// can be compared with many others.
+struct Fingerprint {
+    text: String,
+    bigrams: HashMap<(char, char), usize>,
+    total: usize,
+}
+
+impl Fingerprint {
+    fn new(text: &str) -> Self {
+        let text = normalize(text);
+        let bigrams = bigrams(&text);
+        let total = bigrams.values().sum();
+        Self {
+            text,
+            bigrams,
+            total,
+        }
+    }
+
+    /// How alike the two texts are from 0 to 1, as the share of character
+    /// pairs they have in common once case and punctuation are ignored.
+    fn similarity(&self, other: &Fingerprint) -> f64 {
+        if self.text == other.text {
+            return if self.text.is_empty() { 0.0 } else { 1.0 };
+        }
+        let total = self.total + other.total;
+        if total == 0 {
+            return 0.0;
+        }
+        let shared: usize = self
+            .bigrams
+            .iter()
+            .map(|(pair, count)| (*count).min(other.bigrams.get(pair).copied().unwrap_or(0)))
+            .sum();
+        2.0 * shared as f64 / total as f64
+    }
+}
+
+/// The fingerprints of an idea's title and, when it has a description, of
+/// title and description together.
+struct Profile {
+    title: Fingerprint,
+    full: Option<Fingerprint>,
+}
+
+impl Profile {
+    fn new(idea: &Idea) -> Self {
+        let full = (!idea.description.trim().is_empty())
+            .then(|| Fingerprint::new(&format!("{} {}", idea.title, idea.description)));
+        Self {
+            title: Fingerprint::new(&idea.title),
+            full,
+        }
+    }
+
+    /// How alike two ideas are: by title, or by title and description
+    /// together when that says more.
+    fn similarity(&self, other: &Profile) -> f64 {
+        let titles = self.title.similarity(&other.title);
+        match (&self.full, &other.full) {
+            (Some(a), Some(b)) => titles.max(a.similarity(b)),
+            _ => titles,
+        }
+    }
+}
+
+/// A profile with the text it was worked out from, to tell when it's stale.
+struct Cached {
+    title: String,
+    description: String,
+    profile: Profile,
+}
+
+/// Profiles of the stored ideas by id, kept between checks so each idea's is
+/// only worked out again when its title or description changes.
+#[derive(Default)]
+pub struct ProfileCache {
+    profiles: HashMap<u64, Cached>,
+}
+
+impl ProfileCache {
+    /// The existing idea most like `idea`, if any is similar enough.
+    pub fn find_duplicate(&mut self, ideas: &[Idea], idea: &Idea) -> Option<(usize, f64)> {
+        let profile = Profile::new(idea);
+        let mut old = std::mem::take(&mut self.profiles);
+        let mut best: Option<(usize, f64)> = None;
+        for (index, other) in ideas.iter().enumerate() {
+            let cached = match old.remove(&other.id) {
+                Some(cached)
+                    if cached.title == other.title && cached.description == other.description =>
+                {
+                    cached
+                }
+                _ => Cached {
+                    title: other.title.clone(),
+                    description: other.description.clone(),
+                    profile: Profile::new(other),
+                },
+            };
+            let score = profile.similarity(&cached.profile);
+            self.profiles.insert(other.id, cached);
+            if other.id != idea.id
+                && score >= THRESHOLD
+                && best.is_none_or(|(_, best)| score > best)
+            {
+                best = Some((index, score));
+            }
+        }
+        best
+    }
+}
+
+/// Every pair of ideas similar enough to be the same one, most alike first.
+pub fn duplicate_pairs(ideas: &[Idea]) -> Vec<(usize, usize, f64)> {
+    let profiles: Vec<Profile> = ideas.iter().map(Profile::new).collect();
+    let mut pairs = Vec::new();
+    for i in 0..profiles.len() {
+        for j in i + 1..profiles.len() {
+            let score = profiles[i].similarity(&profiles[j]);
+            if score >= THRESHOLD {
+                pairs.push((i, j, score));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+    pairs
+}
+
+/// Checks new ideas against the existing ones before they're added.
+pub trait Handler {
+    /// Holds back `idea` and asks what to do when it looks like one that
+    /// exists, otherwise hands it back.
+    fn check_duplicate(&mut self, idea: Idea) -> Option<Idea>;
+    fn resolve_duplicate(&mut self, choice: Option<usize>);
+}
+
+impl Handler for App {
+    fn check_duplicate(&mut self, idea: Idea) -> Option<Idea> {
+        let Some((index, score)) = self.profiles.find_duplicate(&self.ideas, &idea) else {
+            return Some(idea);
+        };
+        let message = format!(
+            "\"{}\" looks like \"{}\" ({:.0}% alike).",
+            idea.title,
+            self.ideas[index].title,
+            score * 100.0
+        );
+        self.pending_duplicate = Some((idea, self.ideas[index].id));
+        self.open_modal(Modal::select(
+            "Possible duplicate",
+            &message,
+            CHOICES.iter().map(|c| c.to_string()).collect(),
+            Action::Duplicate,
+        ));
+        None
+    }
+
+    /// Acts on the answer to the duplicate modal; `None` goes back to the
+    /// form.
+    fn resolve_duplicate(&mut self, choice: Option<usize>) {
+        let Some((idea, existing)) = self.pending_duplicate.take() else {
+            return;
+        };
+        let Some(choice) = choice else {
+            return;
+        };
+        let Some(index) = self.ideas.iter().position(|i| i.id == existing) else {
+            // The existing idea went away while the modal was open.
+            self.add_new_idea(idea);
+            return;
+        };
+        match choice {
+            0 => {
+                // What was typed stays as the draft the next new idea starts
+                // from.
+                self.close_form();
+                self.active_index = index;
+                self.reveal(index);
+                self.detail_item = 0;
+                self.mode = Mode::Detail;
+                self.set_status("Kept what you wrote as a draft");
+            }
+            1 => {
+                self.ideas[index].absorb(&idea);
+                if let Some(form) = self.form.as_mut() {
+                    form.clear();
+                }
+                let message = format!("Merged into \"{}\"", self.ideas[index].title);
+                self.set_status(&message);
+                self.schedule_save();
+            }
+            _ => self.add_new_idea(idea),
+        }
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_find_duplicate() {
+        use crate::app::dedupe::{Fingerprint, ProfileCache, duplicate_pairs};
+        use crate::app::idea::Idea;
+
+        let text_similarity = |a, b| Fingerprint::new(a).similarity(&Fingerprint::new(b));
+        assert_eq!(text_similarity("Rust TUI!", "rust   tui"), 1.0);
+        assert!(text_similarity("Rust TUI", "Garden shed") < 0.2);
+
+        let mut ideas = vec![
+            Idea::new("Write a terminal dashboard", ""),
+            Idea::new("Plant tomatoes", ""),
+            Idea::new("write a terminal dashboard app", ""),
+        ];
+        for (id, idea) in ideas.iter_mut().enumerate() {
+            idea.id = id as u64 + 1;
+        }
+        let new = Idea::new("Terminal dashboard, write one", "");
+        let mut cache = ProfileCache::default();
+        let mut find = |ideas: &[Idea], idea: &Idea| cache.find_duplicate(ideas, idea);
+        assert_eq!(find(&ideas[..2], &new).map(|d| d.0), Some(0));
+        assert_eq!(find(&ideas, &Idea::new("Learn piano", "")), None);
+        assert_eq!(duplicate_pairs(&ideas).len(), 1);
+
+        // A cached profile is worked out again once its idea changes.
+        ideas[0].title = String::from("Learn the piano");
+        assert_eq!(find(&ideas[..2], &new), None);
+        assert_eq!(
+            find(&ideas[..2], &Idea::new("Learn piano", "")).map(|d| d.0),
+            Some(0)
+        );
+    }
+}
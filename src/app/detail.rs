@@ -0,0 +1,232 @@
+use ratatui::widgets::{Clear, Wrap};
+
+use super::form::IdeaForm;
+use super::links;
+use super::merge::display_value;
+use super::outline::Handler as OutlineHandler;
+use super::state::Handler as StateHandler;
+use super::utils::{format_timestamp, popup_rect};
+use super::{
+    App, Block, Borders, Color, Frame, KeyCode, KeyEvent, Line, Mode, Paragraph, Rect, Span, Style,
+};
+
+/// Something in the detail popup that can be selected.
+#[derive(Clone, Copy, PartialEq)]
+enum Entry {
+    Item(usize),
+    Link(Option<usize>),
+    Backlink(usize),
+}
+
+/// A popup showing everything about the selected idea, where its checklist
+/// items can be ticked off and links to other ideas followed.
+pub trait Handler {
+    fn open_detail(&mut self);
+    fn handle_detail_key(&mut self, key_event: KeyEvent);
+    fn render_detail(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl App {
+    /// The selectable entries of the detail popup: checklist items, then links
+    /// from the description, then the ideas linking here.
+    fn detail_entries(&self) -> Vec<Entry> {
+        let Some(idea) = self.ideas.get(self.active_index) else {
+            return Vec::new();
+        };
+        let items = (0..idea.checklist.len()).map(Entry::Item);
+        let links = links::outgoing(&self.ideas, idea)
+            .into_iter()
+            .map(|(_, target)| Entry::Link(target));
+        let backlinks = links::backlinks(&self.ideas, self.active_index)
+            .into_iter()
+            .map(Entry::Backlink);
+        items.chain(links).chain(backlinks).collect()
+    }
+}
+
+impl Handler for App {
+    fn open_detail(&mut self) {
+        if self.ideas.get(self.active_index).is_some() {
+            self.detail_item = 0;
+            self.mode = Mode::Detail;
+        }
+    }
+
+    fn handle_detail_key(&mut self, key_event: KeyEvent) {
+        if self.ideas.get(self.active_index).is_none() {
+            self.mode = Mode::Read;
+            return;
+        }
+        let entries = self.detail_entries();
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Read,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.detail_item = self.detail_item.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.detail_item + 1 < entries.len() => {
+                self.detail_item += 1;
+            }
+            KeyCode::Char(' ') | KeyCode::Char('x') | KeyCode::Enter => {
+                match entries.get(self.detail_item) {
+                    Some(Entry::Item(item)) => {
+                        let idea = &mut self.ideas[self.active_index];
+                        idea.checklist[*item].done = !idea.checklist[*item].done;
+                        idea.touch();
+                        self.schedule_save();
+                    }
+                    Some(Entry::Link(Some(target))) | Some(Entry::Backlink(target)) => {
+                        self.active_index = *target;
+                        self.detail_item = 0;
+                        self.reveal(*target);
+                    }
+                    Some(Entry::Link(None)) => self.set_status("No idea with that title"),
+                    None => {}
+                }
+            }
+            KeyCode::Char('e') => {
+                let form = IdeaForm::edit(&self.ideas[self.active_index], &self.config.fields);
+                self.open_form(form);
+            }
+            _ => {}
+        }
+    }
+
+    fn render_detail(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(idea) = self.ideas.get(self.active_index) else {
+            return;
+        };
+        let rect = popup_rect(70, 70, (40, 13), (100, 40), area);
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+        let link_style = Style::new().fg(Color::Cyan);
+
+        let mut lines = vec![
+            Line::styled(idea.title.clone(), active_style),
+            Line::styled(
+                format!(
+                    "{}  ·  created {}  ·  updated {}",
+                    idea.status.label(),
+                    format_timestamp(idea.created_at),
+                    format_timestamp(idea.updated_at)
+                ),
+                passive_style,
+            ),
+        ];
+        if !idea.tags.is_empty() {
+            let tags: Vec<String> = idea.tags.iter().map(|t| format!("#{}", t)).collect();
+            lines.push(Line::styled(tags.join(" "), link_style));
+        }
+        // Declared fields first, then values of fields the config dropped.
+        let fields = self
+            .config
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.label()))
+            .chain(
+                idea.extra
+                    .keys()
+                    .filter(|k| !self.config.fields.iter().any(|f| f.name == **k))
+                    .map(|k| (k.as_str(), k.as_str())),
+            );
+        for (name, label) in fields {
+            if let Some(value) = idea.extra.get(name) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{}: ", label), passive_style),
+                    Span::styled(display_value(value), text_style),
+                ]));
+            }
+        }
+        lines.push(Line::from(""));
+        for paragraph in idea.description.lines() {
+            lines.push(Line::styled(paragraph.to_string(), text_style));
+        }
+
+        // Entries are told apart by position: several links can point to the
+        // same idea, or to none.
+        let entry_style = |position: usize, style: Style| {
+            if position == self.detail_item {
+                ("> ", active_style)
+            } else {
+                ("  ", style)
+            }
+        };
+        let mut selected_line = 0;
+
+        if let Some((done, total)) = idea.progress() {
+            lines.push(Line::from(""));
+            lines.push(Line::styled(
+                format!("Checklist {}/{}", done, total),
+                passive_style,
+            ));
+            for (i, item) in idea.checklist.iter().enumerate() {
+                if i == self.detail_item {
+                    selected_line = lines.len();
+                }
+                let (prefix, style) = entry_style(i, text_style);
+                let mark = if item.done { "[x] " } else { "[ ] " };
+                lines.push(Line::from(vec![
+                    Span::styled(prefix, style),
+                    Span::styled(mark, style),
+                    Span::styled(item.text.clone(), style),
+                ]));
+            }
+        }
+
+        let outgoing = links::outgoing(&self.ideas, idea);
+        if !outgoing.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::styled("Links", passive_style));
+            for (i, (title, target)) in outgoing.iter().enumerate() {
+                let position = idea.checklist.len() + i;
+                if position == self.detail_item {
+                    selected_line = lines.len();
+                }
+                let (prefix, style) = entry_style(position, link_style);
+                let text = match target {
+                    Some(target) => self.ideas[*target].title.clone(),
+                    None => format!("{} (missing)", title.trim()),
+                };
+                lines.push(Line::styled(format!("{}{}", prefix, text), style));
+            }
+        }
+
+        let backlinks = links::backlinks(&self.ideas, self.active_index);
+        if !backlinks.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::styled("Linked from", passive_style));
+            for (i, index) in backlinks.into_iter().enumerate() {
+                let position = idea.checklist.len() + outgoing.len() + i;
+                if position == self.detail_item {
+                    selected_line = lines.len();
+                }
+                let (prefix, style) = entry_style(position, link_style);
+                lines.push(Line::styled(
+                    format!("{}{}", prefix, self.ideas[index].title),
+                    style,
+                ));
+            }
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Idea ")
+            .title_bottom(" j/k move  space toggle  enter follow link  e edit  esc close ")
+            .style(passive_style);
+        // Scroll so the selected entry stays in view, counting the rows the
+        // lines above it wrap to.
+        let width = rect.width.saturating_sub(2).max(1) as usize;
+        let height = rect.height.saturating_sub(2) as usize;
+        let rows_above: usize = lines[..selected_line]
+            .iter()
+            .map(|l| l.width().max(1).div_ceil(width))
+            .sum();
+        let scroll = (rows_above + 1).saturating_sub(height) as u16;
+        let para = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0));
+        frame.render_widget(Clear, rect);
+        frame.render_widget(para, rect);
+    }
+}
@@ -1,219 +1,345 @@
+use super::board::Handler as BoardHandler;
+use super::clipboard::Handler as ClipboardHandler;
+use super::detail::Handler as DetailHandler;
+use super::form::{IdeaForm, Outcome};
+use super::history::Handler as HistoryHandler;
+use super::keymap::{Command, command_for, select_command_for};
+use super::modal::{Action, Handler as ModalHandler, Modal};
+use super::outline::{Handler as OutlineHandler, Outline};
+use super::palette::Handler as PaletteHandler;
+use super::regroup::split_description;
+use super::review::Handler as ReviewHandler;
+use super::search::Handler as SearchHandler;
+use super::select::Handler as SelectHandler;
 use super::state::Handler as StateHandler;
+use super::stats::Handler as StatsHandler;
+use super::timeline::Handler as TimelineHandler;
+use super::views::Handler as ViewsHandler;
 use crossterm::event::Event;
+use std::time::{Duration, Instant};
 
-use super::{App, Focus, KeyCode, KeyEvent, KeyEventKind, Mode};
+use super::{
+    App, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, Mode, MouseButton, MouseEvent,
+    MouseEventKind, Position, Rect,
+};
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+const TICK_RATE: Duration = Duration::from_millis(250);
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
 
 pub trait Handler {
     fn handle_key_event(&mut self, key_event: KeyEvent) -> std::io::Result<()>;
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent);
+    fn handle_resize(&mut self, width: u16, height: u16);
+    fn handle_tick(&mut self);
     fn handle_events(&mut self) -> std::io::Result<()>;
 }
 
-impl<'a> Handler for App<'a> {
+impl Handler for App {
+    /// Waits for terminal input until the next tick is due, so timed work runs
+    /// even when no key is pressed. Only input and ticks that change something
+    /// mark the app dirty, keeping an idle app from redrawing.
     fn handle_events(&mut self) -> std::io::Result<()> {
-        match crossterm::event::read()? {
-            Event::Key(key_event) => {
-                if key_event.kind == KeyEventKind::Press {
+        let timeout = TICK_RATE.saturating_sub(self.last_tick.elapsed());
+        if crossterm::event::poll(timeout)? {
+            match crossterm::event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                     self.handle_key_event(key_event)?;
                 }
+                Event::Mouse(mouse_event) if mouse_event.kind != MouseEventKind::Moved => {
+                    self.handle_mouse_event(mouse_event)
+                }
+                Event::Resize(width, height) => self.handle_resize(width, height),
+                Event::Paste(text) => self.paste(&text),
+                _ => return Ok(()),
             }
-            _ => {}
+            self.dirty = true;
+        }
+
+        if self.last_tick.elapsed() >= TICK_RATE {
+            self.last_tick = Instant::now();
+            self.handle_tick();
         }
         Ok(())
     }
 
-    fn handle_key_event(&mut self, key_event: KeyEvent) -> std::io::Result<()> {
+    fn handle_tick(&mut self) {
+        if self
+            .status
+            .as_ref()
+            .is_some_and(|s| s.expires_at <= Instant::now())
+        {
+            self.status = None;
+            self.dirty = true;
+        }
+
+        if self.locked {
+            return;
+        }
+        self.announce_due();
+        if self
+            .pending_save
+            .is_some_and(|at| at.elapsed() >= SAVE_DEBOUNCE)
+        {
+            self.save();
+        } else if self.store.changed_on_disk() {
+            self.reload();
+        }
+    }
+
+    fn handle_resize(&mut self, width: u16, height: u16) {
+        // Re-wrap the popup buffers right away so cursor positions match the new
+        // size, and drop the list geometry until the next frame recomputes it.
+        let area = Rect::new(0, 0, width, height);
+        if let Some(form) = self.form.as_mut() {
+            form.layout(area);
+        }
+        if let Some(draft) = self.draft.as_mut() {
+            draft.layout(area);
+        }
+        self.list_area = Rect::default();
+        self.list_rows.clear();
+    }
+
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        if !self.modals.is_empty() {
+            return;
+        }
+
+        let (column, row) = (mouse_event.column, mouse_event.row);
         match self.mode {
-            Mode::Read => match key_event.code {
-                KeyCode::Char('q') => {
-                    self.quit();
-                    let i = self.active_index;
-                    std::fs::write(&self.active_index_path, i.to_string())?;
-                }
-                KeyCode::Char('d') => {
-                    let index = self.active_index;
-                    if index == self.ideas.len() - 1 {
-                        self.active_index -= 1;
-                    }
-                    self.remove_idea(index);
-                    self.save();
-                }
-                KeyCode::Up => {
-                    if self.active_index != 0 {
-                        self.active_index -= 1;
-                    }
-                }
-                KeyCode::Char('k') => {
-                    if self.active_index != 0 {
-                        self.active_index -= 1;
-                    }
-                }
-                KeyCode::Down => {
-                    if self.active_index != self.ideas.len() - 1 {
-                        self.active_index += 1;
-                    }
-                }
-                KeyCode::Char('j') => {
-                    if self.active_index != self.ideas.len() - 1 {
-                        self.active_index += 1;
-                    }
-                }
-                KeyCode::Char('e') | KeyCode::Char('c') => {
-                    self.focus = Some(Focus::Title);
-                    self.load_buffer();
-                    self.mode = Mode::Edit;
-                }
-                KeyCode::Char('a') | KeyCode::Char('i') => {
-                    self.mode = Mode::Write;
-                    self.focus = Some(Focus::Title);
-                }
+            Mode::Read if self.board => match mouse_event.kind {
+                MouseEventKind::ScrollUp => self.board_move(-1, 0),
+                MouseEventKind::ScrollDown => self.board_move(1, 0),
                 _ => {}
             },
-            Mode::Write => match key_event.code {
-                KeyCode::Char(char) => {
-                    if let Some(focus) = &self.focus {
-                        match focus {
-                            Focus::Title => {
-                                self.buffer[0].push(char);
-                                self.title_view.set_buffer(&self.buffer[0]);
-                                self.cursor_position =
-                                    self.title_view.get_last_word_cursor_position();
-                            }
-
-                            Focus::Description => {
-                                self.buffer[1].push(char);
-                                self.description_view.set_buffer(&self.buffer[1]);
-                                self.cursor_position =
-                                    self.description_view.get_last_word_cursor_position();
-                            }
-                            _ => {}
-                        }
+            Mode::Read => match mouse_event.kind {
+                MouseEventKind::ScrollUp => self.select_previous(),
+                MouseEventKind::ScrollDown => self.select_next(),
+                MouseEventKind::Down(MouseButton::Left) => {
+                    if !self.list_area.contains(Position::new(column, row)) {
+                        return;
                     }
-                }
+                    let line = (row - self.list_area.y + self.list_offset) as usize;
+                    let Some(index) = self.list_rows.get(line).copied() else {
+                        return;
+                    };
 
-                KeyCode::Enter => {
-                    let title = &self.buffer[0].clone();
-                    let description = &self.buffer[1].clone();
-                    self.add_idea(title, description);
-                    self.clear_buffer();
-                    self.focus = Some(Focus::Title);
-                    self.save()
-                }
-
-                KeyCode::Backspace => {
-                    if let Some(focus) = &self.focus {
-                        match focus {
-                            Focus::Title => {
-                                self.buffer[0].pop();
-                            }
-                            Focus::Description => {
-                                self.buffer[1].pop();
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                KeyCode::Tab => {
-                    if let Some(focus) = &self.focus {
-                        match focus {
-                            Focus::Title => {
-                                self.focus = {
-                                    let sanitized = self.buffer[0].trim();
-                                    if sanitized != "" {
-                                        Some(Focus::Description)
-                                    } else {
-                                        Some(Focus::Title)
-                                    }
-                                }
-                            }
-                            Focus::Description => {
-                                self.focus = Some(Focus::Add);
-                            }
-                            Focus::Add => self.focus = Some(Focus::Title),
-                        }
+                    let double_click = self
+                        .last_click
+                        .is_some_and(|(at, i)| i == index && at.elapsed() < DOUBLE_CLICK);
+                    self.active_index = index;
+                    if double_click {
+                        self.last_click = None;
+                        let form = IdeaForm::edit(&self.ideas[index], &self.config.fields);
+                        self.open_form(form);
+                    } else {
+                        self.last_click = Some((Instant::now(), index));
                     }
                 }
-                KeyCode::Esc => {
-                    self.mode = Mode::Read;
-                    self.focus = None;
-                }
-                KeyCode::Left => {
-                    self.cursor_position.0 = self.cursor_position.0.saturating_sub(1);
+                _ => {}
+            },
+            Mode::History => match mouse_event.kind {
+                MouseEventKind::ScrollUp => {
+                    self.history_index = self.history_index.saturating_sub(1);
                 }
-                KeyCode::Right => {
-                    self.cursor_position.0 = self.cursor_position.0.saturating_add(1);
+                MouseEventKind::ScrollDown if self.history_index + 1 < self.history.len() => {
+                    self.history_index += 1;
                 }
                 _ => {}
             },
-            Mode::Edit => match key_event.code {
-                KeyCode::Char(char) => {
-                    if let Some(focus) = &self.focus {
-                        match focus {
-                            Focus::Title => {
-                                self.buffer[0].push(char);
-                                self.cursor_position.0 = self.cursor_position.0.saturating_add(1);
-                            }
-                            Focus::Description => {
-                                self.buffer[1].push(char);
-                                self.cursor_position.0 = self.cursor_position.0.saturating_add(1);
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                KeyCode::Enter => {
-                    self.save_edit();
-                    self.clear_buffer();
-                    self.focus = Some(Focus::Title);
-                    self.save()
+            Mode::Detail
+            | Mode::Timeline
+            | Mode::Review
+            | Mode::Stats
+            | Mode::Palette
+            | Mode::Search => {}
+            Mode::Form => {
+                if let MouseEventKind::Down(MouseButton::Left) = mouse_event.kind
+                    && let Some(form) = self.form.as_mut()
+                    && form.handle_click(column, row) == Some(Outcome::Submit)
+                {
+                    self.submit_form();
                 }
-                KeyCode::Backspace => {
-                    if let Some(focus) = &self.focus {
-                        match focus {
-                            Focus::Title => {
-                                self.buffer[0].pop();
-                            }
-                            Focus::Description => {
-                                self.buffer[1].pop();
-                            }
-                            _ => {}
-                        }
-                    }
+            }
+        }
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> std::io::Result<()> {
+        if !self.modals.is_empty() {
+            return self.handle_modal_key(key_event);
+        }
+        if self.mode == Mode::Read {
+            if self.selecting
+                && let Some(command) = select_command_for(key_event)
+            {
+                return self.perform(command);
+            }
+            if self.board && self.handle_board_key(key_event) {
+                return Ok(());
+            }
+        }
+
+        match self.mode {
+            Mode::Read => {
+                if let Some(command) = command_for(key_event) {
+                    self.perform(command)?;
                 }
-                KeyCode::Tab => {
-                    if let Some(focus) = &self.focus {
-                        match focus {
-                            Focus::Title => {
-                                self.focus = {
-                                    let sanitized = self.buffer[0].trim();
-                                    if sanitized != "" {
-                                        self.cursor_position.0 = self.buffer[1].len() as u16;
-                                        Some(Focus::Description)
-                                    } else {
-                                        Some(Focus::Title)
-                                    }
-                                }
-                            }
-                            Focus::Description => {
-                                self.focus = Some(Focus::Add);
-                                self.cursor_position.0 = self.buffer[1].len() as u16;
-                            }
-                            Focus::Add => self.focus = Some(Focus::Title),
-                        }
-                    }
+            }
+            Mode::Form
+                if key_event.code == KeyCode::Char('v')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                if let Some(text) = self.clipboard.clone() {
+                    self.paste(&text);
                 }
-                KeyCode::Esc => {
-                    self.mode = Mode::Read;
-                    self.focus = None;
+            }
+            Mode::Form => {
+                let outcome = match self.form.as_mut() {
+                    Some(form) => form.handle_key(key_event),
+                    None => Some(Outcome::Cancel),
+                };
+                match outcome {
+                    Some(Outcome::Submit) => self.submit_form(),
+                    Some(Outcome::Cancel) => self.close_form(),
+                    None => {}
                 }
-                KeyCode::Left => {
-                    self.cursor_position.0 = self.cursor_position.0.saturating_sub(1);
+            }
+            Mode::History => self.handle_history_key(key_event),
+            Mode::Detail => self.handle_detail_key(key_event),
+            Mode::Timeline => self.handle_timeline_key(key_event),
+            Mode::Review => self.handle_review_key(key_event),
+            Mode::Stats => self.handle_stats_key(key_event),
+            Mode::Palette => self.handle_palette_key(key_event)?,
+            Mode::Search => self.handle_search_key(key_event),
+        }
+        Ok(())
+    }
+}
+
+/// What the keys in the list do, shared with the command palette.
+impl App {
+    pub fn perform(&mut self, command: Command) -> std::io::Result<()> {
+        match command {
+            Command::Quit => return self.request_quit(),
+            Command::Delete => self.confirm_delete(),
+            Command::Previous => self.select_previous(),
+            Command::Next => self.select_next(),
+            Command::Collapse => self.collapse(),
+            Command::Expand => self.expand(),
+            Command::ToggleCollapsed => self.toggle_collapsed(),
+            Command::Indent => self.indent(),
+            Command::Outdent => self.outdent(),
+            Command::CycleStatus => self.cycle_status(),
+            Command::Edit => self.edit_idea(),
+            Command::History => self.open_history(),
+            Command::Open => self.open_detail(),
+            Command::Timeline => self.open_timeline(),
+            Command::Review => self.open_review(),
+            Command::Board => self.toggle_board(),
+            Command::Stats => self.open_stats(),
+            Command::Filter => self.open_filter_prompt(),
+            Command::ClearFilter => {
+                if self.filter.is_some() {
+                    self.set_filter("");
                 }
-                KeyCode::Right => {
-                    self.cursor_position.0 = self.cursor_position.0.saturating_sub(1);
+            }
+            Command::NextView => self.cycle_view(1),
+            Command::PreviousView => self.cycle_view(-1),
+            Command::Search => self.open_search(),
+            Command::Palette => self.open_palette(),
+            Command::Copy => self.open_copy_menu(),
+            Command::CopyTitle => self.copy_active(0),
+            Command::CopyDescription => self.copy_active(1),
+            Command::CopyMarkdown => self.copy_active(2),
+            Command::Split => self.confirm_split(),
+            Command::ToggleArchived => self.toggle_archived(),
+            Command::New => self.new_idea(),
+            Command::Select => self.start_selecting(),
+            Command::SelectAll => {
+                self.start_selecting();
+                self.select_all();
+            }
+            Command::StopSelecting => self.stop_selecting(),
+            Command::ToggleSelected => self.toggle_selected(),
+            Command::SelectRange => self.select_range(),
+            Command::ArchiveSelected => {
+                if self.has_selection() {
+                    self.archive_selected();
                 }
-                _ => {}
-            },
+            }
+            Command::DeleteSelected
+            | Command::MergeSelected
+            | Command::TagSelected
+            | Command::UntagSelected
+            | Command::StatusSelected
+            | Command::ExportSelected => self.confirm_selected(command),
         }
         Ok(())
     }
+
+    pub fn request_quit(&mut self) -> std::io::Result<()> {
+        if self.has_unsaved_form() {
+            self.open_modal(Modal::confirm(
+                "Quit",
+                "The idea popup has unsaved contents. Quit anyway?",
+                Action::Quit,
+            ));
+            Ok(())
+        } else {
+            self.quit()
+        }
+    }
+
+    pub fn confirm_delete(&mut self) {
+        if let Some(idea) = self.ideas.get(self.active_index) {
+            let mut message = format!("Delete \"{}\"?", idea.title);
+            let children = Outline::new(&self.ideas).children(idea.id).len();
+            if children > 0 {
+                message.push_str(&format!(" Its {} sub-ideas move up a level.", children));
+            }
+            let action = Action::DeleteIdea(idea.id);
+            self.open_modal(Modal::confirm("Delete", &message, action));
+        }
+    }
+
+    pub fn edit_idea(&mut self) {
+        if let Some(idea) = self.ideas.get(self.active_index) {
+            let form = IdeaForm::edit(idea, &self.config.fields);
+            self.open_form(form);
+        }
+    }
+
+    pub fn new_idea(&mut self) {
+        let form = self
+            .draft
+            .take()
+            .unwrap_or_else(|| IdeaForm::create(&self.config.fields));
+        self.open_form(form);
+    }
+
+    pub fn confirm_split(&mut self) {
+        if let Some(idea) = self.ideas.get(self.active_index) {
+            let parts = split_description(&idea.description).0.len();
+            let message = format!(
+                "Split the description of \"{}\" into {} sub-ideas?",
+                idea.title, parts
+            );
+            if parts < 2 {
+                self.set_status("Nothing to split, the description needs bullets or paragraphs");
+            } else {
+                self.open_modal(Modal::confirm("Split", &message, Action::SplitIdea));
+            }
+        }
+    }
+
+    pub fn toggle_archived(&mut self) {
+        self.show_archived = !self.show_archived;
+        let message = if self.show_archived {
+            "Showing archived ideas"
+        } else {
+            "Hiding archived ideas"
+        };
+        self.set_status(message);
+    }
 }
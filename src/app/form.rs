@@ -0,0 +1,600 @@
+use ratatui::{layout::Margin, widgets::Clear};
+
+use super::buffer::View;
+use super::config::{FieldConfig, FieldKind};
+use super::dates::parse_date;
+use super::idea::{checklist_from_text, checklist_to_text, tags_from_text};
+use super::merge::display_value;
+use super::utils::today;
+use super::{
+    Block, Borders, Color, Constraint, Direction, Frame, Idea, KeyCode, KeyEvent, KeyModifiers,
+    Layout, Paragraph, Position, Rect, Style,
+};
+use crate::app::utils::popup_rect;
+use chrono::NaiveDate;
+
+pub const TITLE: usize = 0;
+pub const DESCRIPTION: usize = 1;
+pub const CHECKLIST: usize = 2;
+pub const TAGS: usize = 3;
+pub const DUE: usize = 4;
+pub const REVIEW: usize = 5;
+/// The first of the fields declared in the config.
+pub const EXTRA: usize = 6;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Focus {
+    Field(usize),
+    Add,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Kind {
+    Create,
+    Edit(u64),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Outcome {
+    Submit,
+    Cancel,
+}
+
+/// What a field accepts, checked when the form is submitted.
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Input {
+    Text,
+    /// Empty, or anything `parse_date` understands.
+    Date,
+    Number,
+    /// Something like `https://example.com`.
+    Url,
+    /// One of `Field::options`.
+    Choice,
+}
+
+struct Field {
+    label: String,
+    view: View,
+    multiline: bool,
+    required: bool,
+    input: Input,
+    /// Shares a row with the field before it.
+    inline: bool,
+    options: Vec<String>,
+}
+
+impl Field {
+    fn new(label: &str, multiline: bool, required: bool) -> Self {
+        Self {
+            label: label.to_string(),
+            view: View::new(),
+            multiline,
+            required,
+            input: Input::Text,
+            inline: false,
+            options: Vec::new(),
+        }
+    }
+
+    fn date(label: &str, inline: bool) -> Self {
+        Self {
+            input: Input::Date,
+            inline,
+            ..Self::new(label, false, false)
+        }
+    }
+
+    /// An input for a field declared in the config.
+    fn extra(config: &FieldConfig, inline: bool) -> Self {
+        let (input, hint) = match config.kind {
+            FieldKind::String => (Input::Text, String::new()),
+            FieldKind::Number => (Input::Number, String::from("number")),
+            FieldKind::Enum => (Input::Choice, config.options.join("/")),
+            FieldKind::Date => (Input::Date, String::from("date")),
+            FieldKind::Url => (Input::Url, String::from("url")),
+        };
+        let label = if hint.is_empty() {
+            config.label().to_string()
+        } else {
+            format!("{} · {}", config.label(), hint)
+        };
+        Self {
+            input,
+            inline,
+            options: config.options.clone(),
+            ..Self::new(&label, false, false)
+        }
+    }
+
+    /// The problem with the current contents, if any.
+    fn check(&self) -> Option<String> {
+        let text = self.view.text().trim();
+        if text.is_empty() {
+            return self
+                .required
+                .then(|| format!("{} can't be empty", self.label));
+        }
+        let error = match self.input {
+            Input::Text => None,
+            Input::Date => parse_date(text, today()).err(),
+            Input::Number => text
+                .parse::<f64>()
+                .err()
+                .map(|_| format!("{:?} isn't a number", text)),
+            Input::Url => (!is_url(text)).then(|| format!("{:?} isn't a url", text)),
+            Input::Choice => choice(&self.options, text)
+                .is_none()
+                .then(|| format!("pick one of {}", self.options.join(", "))),
+        };
+        error.map(|err| format!("{}: {}", self.label, err))
+    }
+}
+
+/// Whether `text` looks like a web address with a scheme and a host.
+fn is_url(text: &str) -> bool {
+    text.split_once("://").is_some_and(|(scheme, rest)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+            && !rest.is_empty()
+            && !rest.contains(char::is_whitespace)
+    })
+}
+
+/// The option `text` names, ignoring case.
+fn choice<'a>(options: &'a [String], text: &str) -> Option<&'a String> {
+    options.iter().find(|o| o.eq_ignore_ascii_case(text))
+}
+
+/// The stored value of a field declared in the config, from what was typed.
+fn extra_value(config: &FieldConfig, text: &str) -> serde_json::Value {
+    let text = text.trim();
+    match config.kind {
+        FieldKind::Number => match text.parse::<i64>() {
+            Ok(number) => number.into(),
+            Err(_) => text
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(serde_json::Value::Number)
+                .unwrap_or_else(|| text.into()),
+        },
+        FieldKind::Date => parse_date(text, today())
+            .map(|d| d.to_string())
+            .unwrap_or_else(|_| text.to_string())
+            .into(),
+        FieldKind::Enum => choice(&config.options, text)
+            .map(String::as_str)
+            .unwrap_or(text)
+            .into(),
+        FieldKind::String | FieldKind::Url => text.into(),
+    }
+}
+
+/// The popup used both to write new ideas and to edit existing ones.
+pub struct IdeaForm {
+    kind: Kind,
+    original: Option<Idea>,
+    fields: Vec<Field>,
+    /// The config's fields, in the order of the inputs from `EXTRA` on.
+    extra: Vec<FieldConfig>,
+    focus: Focus,
+    error: Option<String>,
+    rect: Rect,
+    field_areas: Vec<Rect>,
+    error_area: Rect,
+    button_area: Rect,
+}
+
+impl IdeaForm {
+    pub fn create(extra: &[FieldConfig]) -> Self {
+        let mut fields = vec![
+            Field::new("Title", false, true),
+            Field::new("Description", true, false),
+            Field::new("Checklist · one item per line", true, false),
+            Field::new("Tags · e.g. rust, tui", false, false),
+            Field::date("Due · e.g. next friday", false),
+            Field::date("Review · e.g. in 2w", true),
+        ];
+        // Two to a row.
+        for (i, config) in extra.iter().enumerate() {
+            fields.push(Field::extra(config, i % 2 == 1));
+        }
+        Self {
+            kind: Kind::Create,
+            original: None,
+            fields,
+            extra: extra.to_vec(),
+            focus: Focus::Field(TITLE),
+            error: None,
+            rect: Rect::default(),
+            field_areas: Vec::new(),
+            error_area: Rect::default(),
+            button_area: Rect::default(),
+        }
+    }
+
+    pub fn edit(idea: &Idea, extra: &[FieldConfig]) -> Self {
+        let mut form = Self::create(extra);
+        form.kind = Kind::Edit(idea.id);
+        form.original = Some(idea.clone());
+        form.fields[TITLE].view.set_buffer(&idea.title);
+        form.fields[DESCRIPTION].view.set_buffer(&idea.description);
+        form.fields[CHECKLIST]
+            .view
+            .set_buffer(&checklist_to_text(&idea.checklist));
+        form.fields[TAGS].view.set_buffer(&idea.tags.join(", "));
+        let date = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+        form.fields[DUE].view.set_buffer(&date(idea.due));
+        form.fields[REVIEW].view.set_buffer(&date(idea.review));
+        for (i, config) in extra.iter().enumerate() {
+            if let Some(value) = idea.extra.get(&config.name) {
+                form.fields[EXTRA + i]
+                    .view
+                    .set_buffer(&display_value(value));
+            }
+        }
+        form
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// The idea as it was when editing started.
+    pub fn original(&self) -> Option<&Idea> {
+        self.original.as_ref()
+    }
+
+    /// Records that the idea being edited changed underneath the form.
+    pub fn rebase(&mut self, idea: Idea) {
+        self.original = Some(idea);
+    }
+
+    pub fn title(&self) -> &str {
+        self.fields[TITLE].view.text().trim()
+    }
+
+    pub fn description(&self) -> &str {
+        self.fields[DESCRIPTION].view.text().trim()
+    }
+
+    /// Copies the form contents onto `idea`.
+    pub fn apply(&self, idea: &mut Idea) {
+        idea.title = self.title().to_string();
+        idea.description = self.description().to_string();
+        idea.checklist = checklist_from_text(self.fields[CHECKLIST].view.text());
+        idea.tags = tags_from_text(self.fields[TAGS].view.text());
+        idea.due = self.date(DUE);
+        idea.review = self.date(REVIEW);
+        // Values of fields that aren't in the config are left alone.
+        for (i, config) in self.extra.iter().enumerate() {
+            let text = self.fields[EXTRA + i].view.text().trim();
+            if text.is_empty() {
+                idea.extra.remove(&config.name);
+            } else {
+                idea.extra
+                    .insert(config.name.clone(), extra_value(config, text));
+            }
+        }
+    }
+
+    /// The date in a date field; empty or unreadable is no date.
+    fn date(&self, index: usize) -> Option<NaiveDate> {
+        parse_date(self.fields[index].view.text(), today()).ok()
+    }
+
+    pub fn has_unsaved(&self) -> bool {
+        self.fields.iter().any(|f| !f.view.text().trim().is_empty())
+    }
+
+    pub fn clear(&mut self) {
+        for field in self.fields.iter_mut() {
+            field.view.set_buffer("");
+        }
+        self.focus = Focus::Field(TITLE);
+        self.error = None;
+    }
+
+    /// Checks required fields and dates, moving focus to the first invalid one.
+    pub fn validate(&mut self) -> bool {
+        let invalid = self
+            .fields
+            .iter()
+            .enumerate()
+            .find_map(|(i, f)| f.check().map(|error| (i, error)));
+        match invalid {
+            Some((index, error)) => {
+                self.error = Some(error);
+                self.focus = Focus::Field(index);
+                false
+            }
+            None => {
+                self.error = None;
+                true
+            }
+        }
+    }
+
+    fn focused_view(&mut self) -> Option<&mut View> {
+        match self.focus {
+            Focus::Field(index) => Some(&mut self.fields[index].view),
+            Focus::Add => None,
+        }
+    }
+
+    fn next_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Field(index) => {
+                let field = &self.fields[index];
+                if field.required && field.view.text().trim().is_empty() {
+                    Focus::Field(index)
+                } else if index + 1 < self.fields.len() {
+                    Focus::Field(index + 1)
+                } else {
+                    Focus::Add
+                }
+            }
+            Focus::Add => Focus::Field(TITLE),
+        }
+    }
+
+    fn previous_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Field(0) => Focus::Add,
+            Focus::Field(index) => Focus::Field(index - 1),
+            Focus::Add => Focus::Field(self.fields.len() - 1),
+        }
+    }
+
+    /// Inserts pasted text into the focused field. One-line fields get the
+    /// lines joined by spaces.
+    pub fn paste(&mut self, text: &str) {
+        let multiline = matches!(self.focus, Focus::Field(i) if self.fields[i].multiline);
+        let text = text.trim_end_matches(['\r', '\n']);
+        let text = if multiline {
+            text.to_string()
+        } else {
+            text.lines().collect::<Vec<_>>().join(" ")
+        };
+        if let Some(view) = self.focused_view() {
+            view.insert_str(&text);
+        }
+    }
+
+    /// Feeds a key to the form. Returns `Some` when the form was submitted or
+    /// dismissed. Enter submits; in multiline fields Alt/Shift+Enter or Ctrl-J
+    /// start a new line.
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> Option<Outcome> {
+        let multiline = matches!(self.focus, Focus::Field(i) if self.fields[i].multiline);
+        let newline = match key_event.code {
+            KeyCode::Enter => key_event
+                .modifiers
+                .intersects(KeyModifiers::ALT | KeyModifiers::SHIFT),
+            KeyCode::Char('j') => key_event.modifiers.contains(KeyModifiers::CONTROL),
+            _ => false,
+        };
+        if newline {
+            if multiline && let Some(view) = self.focused_view() {
+                view.insert('\n');
+            }
+            return None;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => return Some(Outcome::Cancel),
+            KeyCode::Enter => {
+                if self.validate() {
+                    return Some(Outcome::Submit);
+                }
+            }
+            KeyCode::Tab => self.next_focus(),
+            KeyCode::BackTab => self.previous_focus(),
+            code => {
+                if let Some(view) = self.focused_view() {
+                    match code {
+                        KeyCode::Char(char) => view.insert(char),
+                        KeyCode::Backspace => view.backspace(),
+                        KeyCode::Delete => view.delete(),
+                        KeyCode::Left => view.move_left(),
+                        KeyCode::Right => view.move_right(),
+                        KeyCode::Home => view.move_home(),
+                        KeyCode::End => view.move_end(),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Handles a left click at a terminal position: focuses the clicked field
+    /// and places the cursor, or submits when the button was clicked.
+    pub fn handle_click(&mut self, column: u16, row: u16) -> Option<Outcome> {
+        let position = Position::new(column, row);
+        if self.button_area.contains(position) {
+            self.focus = Focus::Add;
+            if self.validate() {
+                return Some(Outcome::Submit);
+            }
+            return None;
+        }
+
+        let clicked = self.field_areas.iter().position(|a| a.contains(position));
+        if let Some(index) = clicked {
+            let area = self.field_areas[index];
+            let view = &mut self.fields[index].view;
+            let scroll = view.scroll_offset(area.height.saturating_sub(2));
+            let x = column.saturating_sub(area.x + 1);
+            let y = row.saturating_sub(area.y + 1) + scroll;
+            view.set_cursor_position(x, y);
+            self.focus = Focus::Field(index);
+        }
+        None
+    }
+
+    /// The smallest terminal the popup fits in with every field showing.
+    pub fn min_size(&self) -> (u16, u16) {
+        let extra_rows = self.extra.len().div_ceil(2) as u16 * 3;
+        (40, 22 + extra_rows)
+    }
+
+    /// Computes where the popup and its parts go inside `area` and re-wraps the
+    /// field buffers to the new widths. Called on every render and on resize.
+    pub fn layout(&mut self, area: Rect) {
+        let extra_rows = self.extra.len().div_ceil(2) as u16 * 3;
+        let rect = popup_rect(50, 75, self.min_size(), (90, 39 + extra_rows), area);
+        let rect_child = rect.inner(Margin::new(2, 1));
+
+        // Fields marked inline share a row with the one before them.
+        let mut rows: Vec<Vec<usize>> = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            match rows.last_mut() {
+                Some(row) if field.inline => row.push(i),
+                _ => rows.push(vec![i]),
+            }
+        }
+
+        let mut constraints: Vec<Constraint> = rows
+            .iter()
+            .map(|row| {
+                if row.iter().any(|i| self.fields[*i].multiline) {
+                    Constraint::Min(3)
+                } else {
+                    Constraint::Length(3)
+                }
+            })
+            .collect();
+        constraints.push(Constraint::Length(1)); // error line
+        constraints.push(Constraint::Length(2)); // button
+
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(rect_child);
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(40),
+                Constraint::Length(14), // button width
+                Constraint::Percentage(40),
+            ])
+            .split(areas[rows.len() + 1]);
+
+        self.field_areas = vec![Rect::default(); self.fields.len()];
+        for (row, row_area) in rows.iter().zip(areas.iter()) {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![Constraint::Ratio(1, row.len() as u32); row.len()])
+                .split(*row_area);
+            for (i, area) in row.iter().zip(columns.iter()) {
+                self.field_areas[*i] = *area;
+                self.fields[*i]
+                    .view
+                    .set_width(area.width.saturating_sub(2).max(1));
+            }
+        }
+
+        self.rect = rect;
+        self.error_area = areas[rows.len()];
+        self.button_area = chunks[1];
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.layout(area);
+
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let error_style = Style::new().fg(Color::Red);
+
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::DarkGray));
+
+        frame.render_widget(Clear, self.rect);
+        frame.render_widget(block, self.rect);
+
+        for (i, field) in self.fields.iter_mut().enumerate() {
+            let field_area = self.field_areas[i];
+            let style = if self.focus == Focus::Field(i) {
+                active_style
+            } else {
+                passive_style
+            };
+            let field_block = Block::default()
+                .borders(Borders::ALL)
+                .title(field.label.as_str())
+                .style(style);
+
+            field.view.render(frame, field_block, field_area);
+
+            if self.focus == Focus::Field(i) {
+                let (x, y) = field.view.cursor_position();
+                let y = y - field
+                    .view
+                    .scroll_offset(field_area.height.saturating_sub(2));
+                frame
+                    .set_cursor_position(Position::new(field_area.x + 1 + x, field_area.y + 1 + y));
+            }
+        }
+
+        if let Some(error) = &self.error {
+            frame.render_widget(
+                Paragraph::new(error.as_str()).style(error_style),
+                self.error_area,
+            );
+        }
+
+        let button_style = if self.focus == Focus::Add {
+            active_style
+        } else {
+            passive_style
+        };
+        let button_label = match self.kind {
+            Kind::Create => "  + Add Idea",
+            Kind::Edit(_) => "  📝 Edit Idea",
+        };
+        let button_block = Block::default().borders(Borders::NONE).style(button_style);
+        let button_text = Paragraph::new(button_label).block(button_block);
+
+        frame.render_widget(button_text, self.button_area);
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_extra_fields() {
+        use crate::app::config::Config;
+        use crate::app::form::{EXTRA, IdeaForm};
+        use crate::app::idea::Idea;
+
+        let config: Config = serde_json::from_str(
+            r#"{"fields": [
+                {"name": "effort", "type": "number"},
+                {"name": "stack", "type": "enum", "options": ["Rust", "Go"]},
+                {"name": "repo", "type": "url"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut idea = Idea::new("Dashboard", "");
+        idea.extra.insert("old".into(), "kept".into());
+
+        let mut form = IdeaForm::edit(&idea, &config.fields);
+        form.fields[EXTRA].view.set_buffer("3.5");
+        form.fields[EXTRA + 1].view.set_buffer("rust");
+        form.fields[EXTRA + 2].view.set_buffer("github.com/x");
+        assert!(!form.validate());
+
+        form.fields[EXTRA + 2]
+            .view
+            .set_buffer("https://github.com/x");
+        assert!(form.validate());
+        form.apply(&mut idea);
+        assert_eq!(idea.extra["effort"], serde_json::json!(3.5));
+        assert_eq!(idea.extra["stack"], "Rust");
+        assert_eq!(idea.extra["old"], "kept");
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::crypto::is_encrypted;
+use super::idea::Idea;
+use super::merge::{Conflict, merge};
+use super::store::parse_ideas;
+
+/// One commit that touched the store.
+#[derive(Clone, Debug)]
+pub struct Revision {
+    pub hash: String,
+    pub time: u64,
+    pub message: String,
+}
+
+/// Why an encrypted store gets no history: every save seals it with a fresh
+/// nonce, so git would store unreadable blobs that change on every save.
+pub const ENCRYPTED: &str = "git history doesn't work with an encrypted store, run `ideas decrypt` or turn off git in ideas.config.json";
+
+/// What `sync` did, for reporting back to the user.
+pub struct SyncReport {
+    pub pulled: bool,
+    pub pushed: bool,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// The repository the store's history is kept in, next to the store. It's
+/// never `.git`, so the history can't end up in a repository that happens to
+/// hold the store's directory, such as the source checkout.
+const GIT_DIR: &str = ".ideas-git";
+
+/// Version history of the store kept in a git repository of its own. Shells
+/// out to the `git` binary, so it works with whatever auth the user has set up.
+pub struct Git {
+    dir: PathBuf,
+    file: String,
+}
+
+impl Git {
+    pub fn new(store_path: &Path) -> Self {
+        let dir = store_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let file = store_path
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("ideas.json"));
+        Self { dir, file }
+    }
+
+    fn run(&self, args: &[&str]) -> std::io::Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(["--git-dir", GIT_DIR, "--work-tree", "."])
+            .args(args)
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::other(format!(
+                "git {}: {}",
+                args.join(" "),
+                stderr.trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Fails when the store on disk is encrypted.
+    pub fn check_plain(&self) -> std::io::Result<()> {
+        match std::fs::read(self.dir.join(&self.file)) {
+            Ok(bytes) if is_encrypted(&bytes) => Err(Error::other(ENCRYPTED)),
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates the store's repository if it doesn't exist yet. Refuses an
+    /// encrypted store.
+    pub fn init(&self) -> std::io::Result<()> {
+        self.check_plain()?;
+        if !self.dir.join(GIT_DIR).exists() {
+            self.run(&["init", "-q"])?;
+        }
+        Ok(())
+    }
+
+    /// Commits the store if it changed. Returns whether a commit was made.
+    pub fn commit(&self, message: &str) -> std::io::Result<bool> {
+        self.check_plain()?;
+        self.run(&["add", "--", &self.file])?;
+        let staged = self.run(&["diff", "--cached", "--name-only", "--", &self.file])?;
+        if staged.trim().is_empty() {
+            return Ok(false);
+        }
+        self.run(&["commit", "-q", "-m", message, "--", &self.file])?;
+        Ok(true)
+    }
+
+    /// Commits that changed the store, newest first.
+    pub fn log(&self) -> std::io::Result<Vec<Revision>> {
+        let output = self.run(&["log", "--format=%H%x1f%ct%x1f%s", "--", &self.file])?;
+        let revisions = output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\x1f');
+                Some(Revision {
+                    hash: parts.next()?.to_string(),
+                    time: parts.next()?.parse().ok()?,
+                    message: parts.next().unwrap_or("").to_string(),
+                })
+            })
+            .collect();
+        Ok(revisions)
+    }
+
+    /// The store as it was at `rev`. A revision without the file is empty.
+    pub fn show(&self, rev: &str) -> std::io::Result<Vec<Idea>> {
+        let listed = self.run(&["ls-tree", "--name-only", rev, "--", &self.file])?;
+        if listed.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let json = self.run(&["show", &format!("{}:{}", rev, self.file)])?;
+        parse_ideas(json.as_bytes())
+    }
+
+    /// Past versions of one idea, newest first, listing only the commits that
+    /// changed it.
+    pub fn history(&self, id: u64) -> std::io::Result<Vec<(Revision, Idea)>> {
+        let mut versions: Vec<(Revision, Idea)> = Vec::new();
+        for revision in self.log()?.into_iter().rev() {
+            let idea = self.show(&revision.hash)?.into_iter().find(|i| i.id == id);
+            if let Some(idea) = idea
+                && versions.last().is_none_or(|(_, last)| *last != idea)
+            {
+                versions.push((revision, idea));
+            }
+        }
+        versions.reverse();
+        Ok(versions)
+    }
+
+    fn current_branch(&self) -> std::io::Result<String> {
+        Ok(self
+            .run(&["symbolic-ref", "--short", "HEAD"])?
+            .trim()
+            .to_string())
+    }
+
+    /// Pulls the remote branch, merging the stores idea by idea instead of line
+    /// by line, then pushes the result.
+    pub fn sync(&self, remote: &str, branch: Option<&str>) -> std::io::Result<SyncReport> {
+        let branch = match branch {
+            Some(branch) => branch.to_string(),
+            None => self.current_branch()?,
+        };
+        let mut report = SyncReport {
+            pulled: false,
+            pushed: false,
+            conflicts: Vec::new(),
+        };
+
+        let remote_has_branch = !self
+            .run(&["ls-remote", "--heads", remote, &branch])?
+            .trim()
+            .is_empty();
+        if remote_has_branch {
+            self.run(&["fetch", "-q", remote, &branch])?;
+            let fetched = self.run(&["rev-parse", "FETCH_HEAD"])?;
+            match self.run(&["rev-parse", "--verify", "-q", "HEAD"]) {
+                // Nothing committed here yet, so just take the remote branch.
+                Err(_) => {
+                    self.run(&["merge", "-q", "--ff-only", "FETCH_HEAD"])?;
+                    report.pulled = true;
+                }
+                Ok(head) => {
+                    let base = self.run(&["merge-base", "HEAD", "FETCH_HEAD"])?;
+                    if base == fetched {
+                        // Nothing new on the remote.
+                    } else if base == head {
+                        self.run(&["merge", "-q", "--ff-only", "FETCH_HEAD"])?;
+                        report.pulled = true;
+                    } else {
+                        report.conflicts = self.merge_fetched(base.trim())?;
+                        report.pulled = true;
+                    }
+                }
+            }
+        }
+
+        let ahead = if remote_has_branch {
+            self.run(&["rev-list", "--count", "FETCH_HEAD..HEAD"])?
+        } else {
+            self.run(&["rev-list", "--count", "HEAD"])
+                .unwrap_or_default()
+        };
+        if !matches!(ahead.trim(), "0" | "") {
+            self.run(&["push", "-q", remote, &format!("HEAD:{}", branch)])?;
+            report.pushed = true;
+        }
+
+        Ok(report)
+    }
+
+    /// Merges FETCH_HEAD into HEAD, resolving the store with the idea merge.
+    /// Conflicts in any other file abort the merge.
+    fn merge_fetched(&self, base: &str) -> std::io::Result<Vec<Conflict>> {
+        let base_ideas = self.show(base)?;
+        let ours = self.show("HEAD")?;
+        let theirs = self.show("FETCH_HEAD")?;
+        let (merged, conflicts) = merge(&base_ideas, &ours, &theirs);
+
+        // A conflicting merge exits non-zero; the unmerged files are checked below.
+        let _ = self.run(&["merge", "-q", "--no-commit", "--no-ff", "FETCH_HEAD"]);
+        let unmerged = self.run(&["diff", "--name-only", "--diff-filter=U"])?;
+        if unmerged.lines().any(|f| !f.ends_with(&self.file)) {
+            let _ = self.run(&["merge", "--abort"]);
+            return Err(Error::other(format!(
+                "merge conflicts outside {}:\n{}",
+                self.file, unmerged
+            )));
+        }
+
+        let json = serde_json::to_string_pretty(&merged)?;
+        std::fs::write(self.dir.join(&self.file), json)?;
+        self.run(&["add", "--", &self.file])?;
+        self.run(&["commit", "-q", "--no-edit"])?;
+        Ok(conflicts)
+    }
+}
+
+/// A commit message describing how the store went from `before` to `after`,
+/// e.g. "add: Socket Programming".
+pub fn describe_changes(before: &[Idea], after: &[Idea]) -> String {
+    let before_map: HashMap<u64, &Idea> = before.iter().map(|i| (i.id, i)).collect();
+    let after_map: HashMap<u64, &Idea> = after.iter().map(|i| (i.id, i)).collect();
+
+    let mut changes = Vec::new();
+    for idea in after {
+        match before_map.get(&idea.id) {
+            None => changes.push(format!("add: {}", idea.title)),
+            Some(old) if *old != idea => changes.push(format!("edit: {}", idea.title)),
+            Some(_) => {}
+        }
+    }
+    for idea in before {
+        if !after_map.contains_key(&idea.id) {
+            changes.push(format!("delete: {}", idea.title));
+        }
+    }
+
+    match changes.len() {
+        0 => String::from("update ideas"),
+        1..=3 => changes.join("; "),
+        n => format!("update {} ideas", n),
+    }
+}
+
+mod tests {
+    #[allow(dead_code)]
+    fn git(dir: &std::path::Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_describe_changes() {
+        use crate::app::git::describe_changes;
+        use crate::app::idea::Idea;
+
+        let a = Idea::new("a", "");
+        let mut b = Idea::new("b", "");
+        let before = vec![a.clone(), b.clone()];
+        b.description = String::from("more");
+        let after = vec![b, Idea::new("c", "")];
+
+        assert_eq!(
+            describe_changes(&before, &after),
+            "edit: b; add: c; delete: a"
+        );
+    }
+
+    #[test]
+    fn test_history_stays_out_of_the_checkout() {
+        use crate::app::crypto::Cipher;
+        use crate::app::git::Git;
+        use crate::app::idea::content_id;
+        use crate::app::store::parse_ideas;
+
+        // A checkout that tracks its own files and the store, like the one
+        // the binary is built in.
+        let root = std::env::temp_dir().join(format!("ideas-git-init-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        git(&root, &["init", "-q"]);
+        git(&root, &["config", "user.name", "Test"]);
+        git(&root, &["config", "user.email", "test@example.com"]);
+        std::fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("ideas.json"), "[]").unwrap();
+        git(&root, &["add", "main.rs", "ideas.json"]);
+        git(&root, &["commit", "-q", "-m", "source"]);
+
+        let store = Git::new(&root.join("ideas.json"));
+        store.init().unwrap();
+        git(
+            &root,
+            &["--git-dir", ".ideas-git", "config", "user.name", "Test"],
+        );
+        git(
+            &root,
+            &[
+                "--git-dir",
+                ".ideas-git",
+                "config",
+                "user.email",
+                "test@example.com",
+            ],
+        );
+        // Written by an older version, without ids.
+        let json = r#"[{"title": "old", "description": ""}]"#;
+        std::fs::write(root.join("ideas.json"), json).unwrap();
+        assert!(store.commit("add: old").unwrap());
+
+        assert_eq!(store.log().unwrap().len(), 1);
+        let id = content_id(&parse_ideas(json.as_bytes()).unwrap()[0]);
+        assert_eq!(store.history(id).unwrap().len(), 1);
+        assert!(store.show("no-such-revision").is_err());
+        let checkout = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(["rev-list", "--count", "HEAD"])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&checkout.stdout).trim(), "1");
+
+        // An encrypted store gets no history.
+        let sealed = Cipher::new("secret").encrypt(b"[]").unwrap();
+        std::fs::write(root.join("ideas.json"), sealed).unwrap();
+        assert!(store.init().is_err());
+        assert!(store.commit("encrypt").is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_sync_against_bare_repo() {
+        use crate::app::git::Git;
+        use crate::app::idea::Idea;
+
+        let root = std::env::temp_dir().join(format!("ideas-git-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let remote = root.join("remote.git");
+        std::fs::create_dir_all(&remote).unwrap();
+        git(&remote, &["init", "-q", "--bare", "-b", "main"]);
+
+        let clone = |name: &str| {
+            let dir = root.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            Git::new(&dir.join("ideas.json")).init().unwrap();
+            let store_git =
+                |args: &[&str]| git(&dir, &[&["--git-dir", ".ideas-git"], args].concat());
+            store_git(&["symbolic-ref", "HEAD", "refs/heads/main"]);
+            store_git(&["config", "user.name", "Test"]);
+            store_git(&["config", "user.email", "test@example.com"]);
+            store_git(&["remote", "add", "origin", remote.to_str().unwrap()]);
+            dir
+        };
+        let save = |dir: &std::path::Path, ideas: &[Idea], message: &str| {
+            let path = dir.join("ideas.json");
+            std::fs::write(&path, serde_json::to_string_pretty(ideas).unwrap()).unwrap();
+            Git::new(&path).commit(message).unwrap();
+        };
+
+        let laptop = clone("laptop");
+        let shared = Idea::new("shared", "");
+        save(&laptop, std::slice::from_ref(&shared), "add: shared");
+        let laptop_git = Git::new(&laptop.join("ideas.json"));
+        assert!(laptop_git.sync("origin", None).unwrap().pushed);
+
+        let desktop = clone("desktop");
+        let desktop_git = Git::new(&desktop.join("ideas.json"));
+        assert!(desktop_git.sync("origin", Some("main")).unwrap().pulled);
+
+        save(
+            &laptop,
+            &[shared.clone(), Idea::new("laptop", "")],
+            "add: laptop",
+        );
+        save(
+            &desktop,
+            &[shared.clone(), Idea::new("desktop", "")],
+            "add: desktop",
+        );
+        laptop_git.sync("origin", None).unwrap();
+        let report = desktop_git.sync("origin", None).unwrap();
+        assert!(report.pulled && report.pushed && report.conflicts.is_empty());
+        laptop_git.sync("origin", None).unwrap();
+
+        let ideas = laptop_git.show("HEAD").unwrap();
+        let titles: Vec<&str> = ideas.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["shared", "desktop", "laptop"]);
+        assert_eq!(laptop_git.history(shared.id).unwrap().len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
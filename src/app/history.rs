@@ -0,0 +1,133 @@
+use ratatui::widgets::{Clear, Wrap};
+
+use super::modal::{Handler as ModalHandler, Modal};
+use super::state::Handler as StateHandler;
+use super::utils::{format_timestamp, popup_rect};
+use super::{
+    App, Block, Borders, Color, Constraint, Direction, Frame, KeyCode, KeyEvent, Layout, Line,
+    Mode, Paragraph, Rect, Span, Style,
+};
+
+/// Browser for the past versions of the selected idea, read from git.
+pub trait Handler {
+    fn open_history(&mut self);
+    fn handle_history_key(&mut self, key_event: KeyEvent);
+    fn render_history(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl Handler for App {
+    fn open_history(&mut self) {
+        let Some(git) = &self.git else {
+            self.open_modal(Modal::message(
+                "History",
+                "Git mode is off. Set \"git\": { \"enabled\": true } in ideas.config.json to keep history.",
+            ));
+            return;
+        };
+        let Some(idea) = self.ideas.get(self.active_index) else {
+            return;
+        };
+
+        match git.history(idea.id) {
+            Ok(history) if history.is_empty() => {
+                self.set_status("No history yet");
+            }
+            Ok(history) => {
+                self.history = history;
+                self.history_index = 0;
+                self.mode = Mode::History;
+            }
+            Err(err) => self.set_status(&format!("Couldn't read history: {}", err)),
+        }
+    }
+
+    fn handle_history_key(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.history.clear();
+                self.mode = Mode::Read;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.history_index = self.history_index.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.history_index + 1 < self.history.len() => {
+                self.history_index += 1;
+            }
+            // Bring the selected version back as the current one.
+            KeyCode::Char('r') => {
+                let Some((_, version)) = self.history.get(self.history_index) else {
+                    return;
+                };
+                let version = version.clone();
+                match self.ideas.iter_mut().find(|i| i.id == version.id) {
+                    Some(idea) => {
+                        *idea = version;
+                        idea.touch();
+                    }
+                    None => self.ideas.push(version),
+                }
+                self.schedule_save();
+                self.set_status("Restored version");
+                self.history.clear();
+                self.mode = Mode::Read;
+            }
+            _ => {}
+        }
+    }
+
+    fn render_history(&mut self, frame: &mut Frame, area: Rect) {
+        let rect = popup_rect(80, 70, (40, 13), (120, 40), area);
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" History ")
+            .title_bottom(" j/k move  r restore  esc close ")
+            .style(passive_style);
+        let inner = block.inner(rect);
+        frame.render_widget(Clear, rect);
+        frame.render_widget(block, rect);
+
+        let [list_area, detail_area] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .areas(inner);
+
+        let lines: Vec<Line> = self
+            .history
+            .iter()
+            .enumerate()
+            .map(|(i, (revision, _))| {
+                let style = if i == self.history_index {
+                    active_style
+                } else {
+                    text_style
+                };
+                Line::from(vec![
+                    Span::styled(format_timestamp(revision.time), passive_style),
+                    Span::styled(format!("  {}", revision.message), style),
+                ])
+            })
+            .collect();
+        let offset = (self.history_index as u16).saturating_sub(list_area.height.saturating_sub(1));
+        frame.render_widget(Paragraph::new(lines).scroll((offset, 0)), list_area);
+
+        if let Some((revision, idea)) = self.history.get(self.history_index) {
+            let detail = vec![
+                Line::styled(idea.title.clone(), active_style),
+                Line::styled(
+                    format!("{}  {}", &revision.hash[..7], revision.message),
+                    passive_style,
+                ),
+                Line::from(""),
+                Line::styled(idea.description.clone(), text_style),
+            ];
+            let para = Paragraph::new(detail)
+                .block(Block::default().borders(Borders::LEFT).style(passive_style))
+                .wrap(Wrap { trim: false });
+            frame.render_widget(para, detail_area);
+        }
+    }
+}
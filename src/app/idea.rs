@@ -0,0 +1,268 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where an idea is in its lifecycle.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    #[default]
+    Inbox,
+    Active,
+    Someday,
+    Done,
+}
+
+impl Status {
+    pub const ALL: [Status; 4] = [Status::Inbox, Status::Active, Status::Someday, Status::Done];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Status::Inbox => "inbox",
+            Status::Active => "active",
+            Status::Someday => "someday",
+            Status::Done => "done",
+        }
+    }
+
+    /// The status after this one, wrapping around, for cycling with a key.
+    pub fn next(self) -> Status {
+        let index = Status::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Status::ALL[(index + 1) % Status::ALL.len()]
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Idea {
+    #[serde(default)]
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub updated_at: u64,
+    #[serde(default)]
+    pub status: Status,
+    /// The idea this one is a sub-idea of.
+    #[serde(default)]
+    pub parent: Option<u64>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// When the idea has to be done by.
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    /// When to look at the idea again.
+    #[serde(default)]
+    pub review: Option<NaiveDate>,
+    /// Seconds since the epoch when the idea was last looked at in a review.
+    #[serde(default)]
+    pub last_reviewed_at: Option<u64>,
+    /// Hidden from the list unless archived ideas are shown.
+    #[serde(default)]
+    pub archived: bool,
+    /// Lowercase labels without spaces.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Values of the fields declared in the config, by field name. Numbers
+    /// are json numbers, everything else strings.
+    #[serde(default)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+    /// The ids of the ideas the `[[Title]]` links in the description point
+    /// to, by lowercase title, worked out when the idea is saved.
+    #[serde(default)]
+    pub links: BTreeMap<String, u64>,
+}
+
+impl Idea {
+    pub fn new(title: &str, description: &str) -> Self {
+        let now = now();
+        Self {
+            id: new_id(),
+            title: String::from(title),
+            description: String::from(description),
+            created_at: now,
+            updated_at: now,
+            status: Status::default(),
+            parent: None,
+            checklist: Vec::new(),
+            due: None,
+            review: None,
+            last_reviewed_at: None,
+            archived: false,
+            tags: Vec::new(),
+            extra: BTreeMap::new(),
+            links: BTreeMap::new(),
+        }
+    }
+
+    /// Done and total checklist items, or `None` without a checklist.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        let done = self.checklist.iter().filter(|i| i.done).count();
+        Some((done, self.checklist.len()))
+    }
+
+    /// Folds `other` into this idea: its description is appended, checklist
+    /// items and tags it adds are kept, missing dates are taken over and the
+    /// earlier creation time wins.
+    pub fn absorb(&mut self, other: &Idea) {
+        let description = other.description.trim();
+        if !description.is_empty() && !self.description.contains(description) {
+            if !self.description.trim().is_empty() {
+                self.description.push_str("\n\n");
+            }
+            self.description.push_str(description);
+        }
+        for item in &other.checklist {
+            if !self.checklist.iter().any(|i| i.text == item.text) {
+                self.checklist.push(item.clone());
+            }
+        }
+        for tag in &other.tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+        for (name, value) in &other.extra {
+            self.extra
+                .entry(name.clone())
+                .or_insert_with(|| value.clone());
+        }
+        for (title, id) in &other.links {
+            self.links.entry(title.clone()).or_insert(*id);
+        }
+        self.due = self.due.or(other.due);
+        self.review = self.review.or(other.review);
+        self.created_at = self.created_at.min(other.created_at);
+        self.touch();
+    }
+
+    /// Marks the idea as changed by a local edit.
+    pub fn touch(&mut self) {
+        self.updated_at = now();
+    }
+}
+
+/// Seconds since the unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Ids stay below 2^53 so json tooling that reads numbers as doubles keeps
+/// them intact.
+const ID_MASK: u64 = (1 << 53) - 1;
+
+/// A fresh id that won't collide with ids created by other processes: the
+/// clock and pid make it unique across instances, the counter within one.
+pub fn new_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let pid = std::process::id() as u64;
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    ((nanos ^ (pid << 32) ^ (count << 16)) & ID_MASK).max(1)
+}
+
+/// Stable id for ideas written without one (older versions, scripts), derived
+/// from their contents so every instance assigns the same id.
+pub fn content_id(idea: &Idea) -> u64 {
+    let bytes = idea
+        .title
+        .bytes()
+        .chain([0])
+        .chain(idea.description.bytes());
+    (fnv1a(bytes) & ID_MASK).max(1)
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, which unlike the std hashers stays the
+/// same across builds and so can be written to disk.
+pub fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Writes a checklist as text for editing, one `[ ]` or `[x]` item per line.
+pub fn checklist_to_text(checklist: &[ChecklistItem]) -> String {
+    checklist
+        .iter()
+        .map(|i| format!("[{}] {}", if i.done { 'x' } else { ' ' }, i.text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reads a checklist back from text: every non-empty line is an item, done
+/// when it starts with `[x]`. A leading `-` or `[ ]` is optional.
+pub fn checklist_from_text(text: &str) -> Vec<ChecklistItem> {
+    text.lines()
+        .map(|line| line.trim().trim_start_matches("- "))
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (done, text) = match line.get(..3) {
+                Some("[x]") | Some("[X]") => (true, &line[3..]),
+                Some("[ ]") => (false, &line[3..]),
+                _ => (false, line),
+            };
+            ChecklistItem {
+                text: text.trim().to_string(),
+                done,
+            }
+        })
+        .filter(|item| !item.text.is_empty())
+        .collect()
+}
+
+/// Reads tags separated by commas or spaces, lowercased, without a leading
+/// `#` and without duplicates.
+pub fn tags_from_text(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split([',', ' ', '\n']) {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+mod tests {
+    #[test]
+    fn test_checklist_text_round_trip() {
+        use crate::app::idea::{checklist_from_text, checklist_to_text};
+
+        let checklist = checklist_from_text("[x] learn X\n\n- build Y\n[ ] ship it");
+        assert_eq!(checklist.len(), 3);
+        assert!(checklist[0].done && !checklist[1].done);
+        assert_eq!(checklist[1].text, "build Y");
+        assert_eq!(
+            checklist_to_text(&checklist),
+            "[x] learn X\n[ ] build Y\n[ ] ship it"
+        );
+    }
+
+    #[test]
+    fn test_tags_from_text() {
+        use crate::app::idea::tags_from_text;
+
+        assert_eq!(tags_from_text("Rust, #tui  rust,"), vec!["rust", "tui"]);
+    }
+}
@@ -0,0 +1,305 @@
+use super::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something the list does, run by a key or from the command palette.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Command {
+    Quit,
+    Delete,
+    Previous,
+    Next,
+    Collapse,
+    Expand,
+    ToggleCollapsed,
+    Indent,
+    Outdent,
+    CycleStatus,
+    Edit,
+    History,
+    Open,
+    Timeline,
+    Review,
+    Board,
+    Stats,
+    Filter,
+    ClearFilter,
+    NextView,
+    PreviousView,
+    Search,
+    Palette,
+    Copy,
+    CopyTitle,
+    CopyDescription,
+    CopyMarkdown,
+    Split,
+    ToggleArchived,
+    New,
+    Select,
+    SelectAll,
+    StopSelecting,
+    ToggleSelected,
+    SelectRange,
+    DeleteSelected,
+    MergeSelected,
+    ArchiveSelected,
+    TagSelected,
+    UntagSelected,
+    StatusSelected,
+    ExportSelected,
+}
+
+impl Command {
+    /// What the command palette calls it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Quit => "Quit",
+            Command::Delete => "Delete idea",
+            Command::Previous => "Previous idea",
+            Command::Next => "Next idea",
+            Command::Collapse => "Collapse",
+            Command::Expand => "Expand",
+            Command::ToggleCollapsed => "Collapse or expand",
+            Command::Indent => "Indent",
+            Command::Outdent => "Outdent",
+            Command::CycleStatus => "Cycle status",
+            Command::Edit => "Edit idea",
+            Command::History => "History",
+            Command::Open => "Open idea",
+            Command::Timeline => "Timeline",
+            Command::Review => "Review stale ideas",
+            Command::Board => "Board",
+            Command::Stats => "Stats",
+            Command::Filter => "Filter",
+            Command::ClearFilter => "Clear filter",
+            Command::NextView => "Next view",
+            Command::PreviousView => "Previous view",
+            Command::Search => "Search",
+            Command::Palette => "Command palette",
+            Command::Copy => "Copy menu",
+            Command::CopyTitle => "Copy title",
+            Command::CopyDescription => "Copy description",
+            Command::CopyMarkdown => "Copy as Markdown",
+            Command::Split => "Split into sub-ideas",
+            Command::ToggleArchived => "Show or hide archived",
+            Command::New => "New idea",
+            Command::Select => "Select ideas",
+            Command::SelectAll => "Select all",
+            Command::StopSelecting => "Stop selecting",
+            Command::ToggleSelected => "Mark or unmark idea",
+            Command::SelectRange => "Mark up to here",
+            Command::DeleteSelected => "Delete selected",
+            Command::MergeSelected => "Merge selected",
+            Command::ArchiveSelected => "Archive selected",
+            Command::TagSelected => "Tag selected",
+            Command::UntagSelected => "Untag selected",
+            Command::StatusSelected => "Set status of selected",
+            Command::ExportSelected => "Export selected",
+        }
+    }
+}
+
+/// A key as the keymap lists it; `control` says whether Ctrl is held.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Key {
+    code: KeyCode,
+    control: bool,
+}
+
+const fn key(c: char) -> Key {
+    Key {
+        code: KeyCode::Char(c),
+        control: false,
+    }
+}
+
+const fn special(code: KeyCode) -> Key {
+    Key {
+        code,
+        control: false,
+    }
+}
+
+const fn control(c: char) -> Key {
+    Key {
+        code: KeyCode::Char(c),
+        control: true,
+    }
+}
+
+/// The keys of the list. The key handler and the palette both read this, so
+/// the keys the palette shows are the ones that work.
+pub const KEYMAP: &[(Key, Command)] = &[
+    (key('q'), Command::Quit),
+    (key('d'), Command::Delete),
+    (special(KeyCode::Up), Command::Previous),
+    (key('k'), Command::Previous),
+    (special(KeyCode::Down), Command::Next),
+    (key('j'), Command::Next),
+    (special(KeyCode::Left), Command::Collapse),
+    (key('h'), Command::Collapse),
+    (special(KeyCode::Right), Command::Expand),
+    (key('l'), Command::Expand),
+    (key(' '), Command::ToggleCollapsed),
+    (key('>'), Command::Indent),
+    (key('<'), Command::Outdent),
+    (key('s'), Command::CycleStatus),
+    (key('e'), Command::Edit),
+    (key('c'), Command::Edit),
+    (key('H'), Command::History),
+    (special(KeyCode::Enter), Command::Open),
+    (key('t'), Command::Timeline),
+    (key('R'), Command::Review),
+    (key('b'), Command::Board),
+    (key('S'), Command::Stats),
+    (key('/'), Command::Filter),
+    (special(KeyCode::Esc), Command::ClearFilter),
+    (special(KeyCode::Tab), Command::NextView),
+    (special(KeyCode::BackTab), Command::PreviousView),
+    (key('f'), Command::Search),
+    (key(':'), Command::Palette),
+    (control('p'), Command::Palette),
+    (key('y'), Command::Copy),
+    (key('X'), Command::Split),
+    (key('A'), Command::ToggleArchived),
+    (key('a'), Command::New),
+    (key('i'), Command::New),
+    (key('v'), Command::Select),
+    (key('*'), Command::SelectAll),
+];
+
+/// Keys that mean something else while selecting. Keys not listed here keep
+/// their meaning from `KEYMAP`.
+pub const SELECT_KEYMAP: &[(Key, Command)] = &[
+    (special(KeyCode::Esc), Command::StopSelecting),
+    (key('v'), Command::StopSelecting),
+    (key(' '), Command::ToggleSelected),
+    (key('V'), Command::SelectRange),
+    (key('*'), Command::SelectAll),
+    (key('d'), Command::DeleteSelected),
+    (key('m'), Command::MergeSelected),
+    (key('a'), Command::ArchiveSelected),
+    (key('t'), Command::TagSelected),
+    (key('T'), Command::UntagSelected),
+    (key('s'), Command::StatusSelected),
+    (key('x'), Command::ExportSelected),
+];
+
+/// Commands only the palette runs, having no key.
+const PALETTE_ONLY: [Command; 3] = [
+    Command::CopyTitle,
+    Command::CopyDescription,
+    Command::CopyMarkdown,
+];
+
+/// The command `key_event` runs in `keymap`.
+fn lookup(keymap: &[(Key, Command)], key_event: KeyEvent) -> Option<Command> {
+    let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    keymap
+        .iter()
+        .find(|(key, _)| key.code == key_event.code && key.control == control)
+        .map(|(_, command)| *command)
+}
+
+/// The command `key_event` runs in the list.
+pub fn command_for(key_event: KeyEvent) -> Option<Command> {
+    lookup(KEYMAP, key_event)
+}
+
+/// The command `key_event` runs while selecting, if it means something
+/// else then.
+pub fn select_command_for(key_event: KeyEvent) -> Option<Command> {
+    lookup(SELECT_KEYMAP, key_event)
+}
+
+/// Every command the palette offers, in keymap order: all of them but the
+/// palette itself and moving the cursor.
+pub fn palette_commands() -> Vec<Command> {
+    let mut commands: Vec<Command> = Vec::new();
+    let all = KEYMAP.iter().chain(SELECT_KEYMAP).map(|(_, c)| *c);
+    for command in all.chain(PALETTE_ONLY) {
+        let skipped = matches!(
+            command,
+            Command::Palette | Command::Previous | Command::Next
+        );
+        if !skipped && !commands.contains(&command) {
+            commands.push(command);
+        }
+    }
+    commands
+}
+
+fn label(key: Key) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => String::from("space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => String::from("enter"),
+        KeyCode::Esc => String::from("esc"),
+        KeyCode::Tab => String::from("tab"),
+        KeyCode::BackTab => String::from("shift-tab"),
+        KeyCode::Up => String::from("↑"),
+        KeyCode::Down => String::from("↓"),
+        KeyCode::Left => String::from("←"),
+        KeyCode::Right => String::from("→"),
+        code => format!("{:?}", code).to_lowercase(),
+    };
+    if key.control {
+        format!("ctrl-{}", name)
+    } else {
+        name
+    }
+}
+
+/// The keys that run `command`, empty when it has none. Keys that only work
+/// while selecting are shown after `v`.
+pub fn keys_for(command: Command) -> String {
+    let labels = |keymap: &[(Key, Command)]| {
+        keymap
+            .iter()
+            .filter(|(_, c)| *c == command)
+            .map(|(key, _)| label(*key))
+            .collect::<Vec<_>>()
+    };
+    let keys = labels(KEYMAP);
+    if !keys.is_empty() {
+        return keys.join(" ");
+    }
+    let keys = labels(SELECT_KEYMAP);
+    if keys.is_empty() {
+        String::new()
+    } else {
+        format!("v then {}", keys.join(" "))
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_keymap() {
+        use crate::app::keymap::{
+            Command, command_for, keys_for, palette_commands, select_command_for,
+        };
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let press = |code, modifiers| command_for(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            press(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            Some(Command::Palette)
+        );
+        assert_eq!(press(KeyCode::Char('p'), KeyModifiers::NONE), None);
+        assert_eq!(
+            press(KeyCode::Char('S'), KeyModifiers::SHIFT),
+            Some(Command::Stats)
+        );
+        assert_eq!(keys_for(Command::Palette), ": ctrl-p");
+        assert_eq!(keys_for(Command::Previous), "↑ k");
+        assert_eq!(keys_for(Command::CopyTitle), "");
+        assert_eq!(keys_for(Command::MergeSelected), "v then m");
+
+        let selecting = |c| select_command_for(KeyEvent::from(KeyCode::Char(c)));
+        assert_eq!(selecting('d'), Some(Command::DeleteSelected));
+        assert_eq!(selecting('e'), None);
+
+        let commands = palette_commands();
+        assert!(commands.contains(&Command::ExportSelected));
+        assert!(commands.contains(&Command::CopyMarkdown));
+        assert!(!commands.contains(&Command::Palette));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use super::idea::Idea;
+
+/// The `[[Title]]` links in `text`, as the byte range of the whole link and
+/// the title inside it.
+pub fn find_links(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut links = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = text[rest..].find("[[").map(|i| rest + i) {
+        let Some(end) = text[start + 2..].find("]]").map(|i| start + 2 + i) else {
+            break;
+        };
+        let title = &text[start + 2..end];
+        // A newline or another opening inside means the brackets don't pair up.
+        if let Some(inner) = title.rfind("[[") {
+            rest = start + 2 + inner;
+            continue;
+        }
+        if !title.trim().is_empty() && !title.contains('\n') {
+            links.push((start..end + 2, title));
+        }
+        rest = end + 2;
+    }
+    links
+}
+
+/// How a link title is keyed in `Idea::links`: trimmed and lowercase.
+fn key(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+/// The idea with a title, ignoring case and surrounding spaces.
+pub fn resolve(ideas: &[Idea], title: &str) -> Option<usize> {
+    let title = key(title);
+    ideas.iter().position(|i| key(&i.title) == title)
+}
+
+/// The ids `idea`'s links point to, for storing in `Idea::links` when it's
+/// saved. Links keep the idea they were made to while it exists; new ones,
+/// and ones to ideas since deleted, are looked up by title.
+pub fn link_ids(ideas: &[Idea], idea: &Idea) -> BTreeMap<String, u64> {
+    let mut ids = BTreeMap::new();
+    for (_, title) in find_links(&idea.description) {
+        let key = key(title);
+        let id = idea
+            .links
+            .get(&key)
+            .copied()
+            .filter(|id| ideas.iter().any(|i| i.id == *id))
+            .or_else(|| resolve(ideas, title).map(|i| ideas[i].id));
+        if let Some(id) = id {
+            ids.insert(key, id);
+        }
+    }
+    ids
+}
+
+/// The idea a link in `idea`'s description points to: the one stored for
+/// it, or for links never saved, the one with its title.
+pub fn target(ideas: &[Idea], idea: &Idea, title: &str) -> Option<usize> {
+    match idea.links.get(&key(title)) {
+        Some(id) => ideas.iter().position(|i| i.id == *id),
+        None => resolve(ideas, title),
+    }
+}
+
+/// Indices of the ideas linked from `idea`'s description, `None` for links
+/// to ideas that don't exist, in the order they appear.
+pub fn outgoing<'a>(ideas: &[Idea], idea: &'a Idea) -> Vec<(&'a str, Option<usize>)> {
+    let mut links: Vec<(&str, Option<usize>)> = Vec::new();
+    for (_, title) in find_links(&idea.description) {
+        if !links.iter().any(|(t, _)| key(t) == key(title)) {
+            links.push((title, target(ideas, idea, title)));
+        }
+    }
+    links
+}
+
+/// Indices of the ideas whose description links to the idea at `index`.
+pub fn backlinks(ideas: &[Idea], index: usize) -> Vec<usize> {
+    ideas
+        .iter()
+        .enumerate()
+        .filter(|(i, idea)| {
+            *i != index
+                && outgoing(ideas, idea)
+                    .iter()
+                    .any(|(_, target)| *target == Some(index))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Points `idea`'s links to the idea with id `from` at the one with id `to`,
+/// rewriting them to `[[title]]`. Links never saved with an id point to
+/// `from` when they name `old_title`, the title it was found by. Returns
+/// whether it had any.
+pub fn retarget(idea: &mut Idea, from: u64, old_title: Option<&str>, to: u64, title: &str) -> bool {
+    let links: Vec<Range<usize>> = find_links(&idea.description)
+        .into_iter()
+        .filter(|(_, t)| match idea.links.get(&key(t)) {
+            Some(id) => *id == from,
+            None => old_title.is_some_and(|old| key(old) == key(t)),
+        })
+        .map(|(range, _)| range)
+        .collect();
+    if links.is_empty() {
+        return false;
+    }
+    let mut text = String::new();
+    let mut last = 0;
+    for range in links {
+        text.push_str(&idea.description[last..range.start]);
+        text.push_str(&format!("[[{}]]", title));
+        last = range.end;
+    }
+    text.push_str(&idea.description[last..]);
+    idea.description = text;
+    idea.links.retain(|_, id| *id != from);
+    idea.links.insert(key(title), to);
+    true
+}
+
+mod tests {
+    #[test]
+    fn test_links_resolve_and_rename() {
+        use crate::app::idea::Idea;
+        use crate::app::links::{backlinks, find_links, link_ids, outgoing, resolve, retarget};
+
+        let text = "Overlaps [[TUI Dashboard]] and [[ missing ]], not [[ or [x].";
+        let titles: Vec<&str> = find_links(text).into_iter().map(|(_, t)| t).collect();
+        assert_eq!(titles, vec!["TUI Dashboard", " missing "]);
+
+        let dashboard = Idea::new("TUI Dashboard", "");
+        let mut app = Idea::new("Improve Ideas App", text);
+        let other = Idea::new("TUI Dashboard v2", "");
+        app.links = link_ids(&[dashboard.clone(), other.clone()], &app);
+        let mut ideas = vec![dashboard, app, other];
+        let links = outgoing(&ideas, &ideas[1]);
+        assert_eq!(links, vec![("TUI Dashboard", Some(0)), (" missing ", None)]);
+        assert_eq!(backlinks(&ideas, 0), vec![1]);
+
+        // Links follow the id, not the title.
+        ideas[0].title = String::from("Dashboard");
+        ideas[2].title = String::from("TUI Dashboard");
+        assert_eq!(outgoing(&ideas, &ideas[1])[0].1, Some(0));
+        assert_eq!(backlinks(&ideas, 2), Vec::<usize>::new());
+
+        let id = ideas[0].id;
+        assert!(retarget(&mut ideas[1], id, None, id, "Dashboard"));
+        assert_eq!(
+            ideas[1].description,
+            "Overlaps [[Dashboard]] and [[ missing ]], not [[ or [x]."
+        );
+        assert_eq!(backlinks(&ideas, 0), vec![1]);
+        let other = ideas[2].id;
+        assert!(!retarget(&mut ideas[1], other, None, id, "x"));
+
+        // Links never saved with an id follow the title they were found by.
+        let mut legacy = Idea::new("Legacy", "See [[tui dashboard]].");
+        assert!(legacy.links.is_empty());
+        assert!(retarget(
+            &mut legacy,
+            other,
+            Some("TUI Dashboard"),
+            other,
+            "Board"
+        ));
+        assert_eq!(legacy.description, "See [[Board]].");
+        assert_eq!(legacy.links.get("board"), Some(&other));
+        assert!(!retarget(&mut legacy, id, None, id, "x"));
+
+        // Titles outside ASCII match whichever way they're looked up.
+        ideas[2].title = String::from("Über");
+        assert_eq!(resolve(&ideas, "über"), Some(2));
+    }
+}
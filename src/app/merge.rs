@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Map, Value};
+
+use super::idea::Idea;
+
+/// A field both sides changed to different values.
+pub struct FieldConflict {
+    pub name: String,
+    pub ours: Value,
+    pub theirs: Value,
+}
+
+/// An idea both sides changed in ways the merge can't settle on its own.
+pub struct Conflict {
+    pub id: u64,
+    /// The merged idea with our value for every conflicting field, or `None`
+    /// if we deleted it.
+    pub ours: Option<Idea>,
+    /// The merged idea with their values, or `None` if they deleted it.
+    pub theirs: Option<Idea>,
+    /// Empty when one side deleted the idea and the other edited it.
+    pub fields: Vec<FieldConflict>,
+}
+
+impl Conflict {
+    pub fn title(&self) -> &str {
+        self.ours
+            .as_ref()
+            .or(self.theirs.as_ref())
+            .map(|i| i.title.as_str())
+            .unwrap_or_default()
+    }
+
+    /// The idea to keep when the fields at the indices in `theirs` take their
+    /// value and all others ours. For a delete conflict index 0 picks their
+    /// side. `None` means the idea is deleted.
+    pub fn resolve(&self, theirs: &[usize]) -> Option<Idea> {
+        let (Some(ours), Some(their)) = (&self.ours, &self.theirs) else {
+            return if theirs.contains(&0) {
+                self.theirs.clone()
+            } else {
+                self.ours.clone()
+            };
+        };
+        let mut map = to_map(ours);
+        for index in theirs {
+            if let Some(field) = self.fields.get(*index) {
+                map.insert(field.name.clone(), field.theirs.clone());
+            }
+        }
+        Some(from_map(map).unwrap_or_else(|| their.clone()))
+    }
+}
+
+/// A field value for showing to the user: strings as they are, anything else
+/// as json.
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => String::from("(none)"),
+        value => value.to_string(),
+    }
+}
+
+fn to_map(idea: &Idea) -> Map<String, Value> {
+    match serde_json::to_value(idea) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn from_map(map: Map<String, Value>) -> Option<Idea> {
+    serde_json::from_value(Value::Object(map)).ok()
+}
+
+/// Merges one idea field by field. Fields changed on one side take that
+/// side's value, `updated_at` takes the later time, and fields both sides
+/// changed differently keep our value and are returned as conflicts.
+fn merge_idea(base: Option<&Idea>, ours: &Idea, theirs: &Idea) -> (Idea, Vec<FieldConflict>) {
+    let base = base.map(to_map).unwrap_or_default();
+    let ours_map = to_map(ours);
+    let theirs_map = to_map(theirs);
+
+    let mut merged = Map::new();
+    let mut conflicts = Vec::new();
+    let keys = ours_map
+        .keys()
+        .chain(theirs_map.keys().filter(|k| !ours_map.contains_key(*k)));
+    for key in keys {
+        let original = base.get(key).unwrap_or(&Value::Null);
+        let our = ours_map.get(key).unwrap_or(&Value::Null);
+        let their = theirs_map.get(key).unwrap_or(&Value::Null);
+        let value = if our == their || their == original {
+            our.clone()
+        } else if our == original {
+            their.clone()
+        } else if key == "updated_at" {
+            ours.updated_at.max(theirs.updated_at).into()
+        } else {
+            conflicts.push(FieldConflict {
+                name: key.clone(),
+                ours: our.clone(),
+                theirs: their.clone(),
+            });
+            our.clone()
+        };
+        merged.insert(key.clone(), value);
+    }
+
+    (from_map(merged).unwrap_or_else(|| ours.clone()), conflicts)
+}
+
+/// Merges two versions of the idea list that both started from `base`,
+/// matching ideas by id. Edits to different fields of the same idea are
+/// combined; fields both sides changed keep our value and are reported as
+/// conflicts, as are ideas one side deleted and the other edited.
+pub fn merge(base: &[Idea], ours: &[Idea], theirs: &[Idea]) -> (Vec<Idea>, Vec<Conflict>) {
+    let base: HashMap<u64, &Idea> = base.iter().map(|i| (i.id, i)).collect();
+    let ours_map: HashMap<u64, &Idea> = ours.iter().map(|i| (i.id, i)).collect();
+    let theirs_map: HashMap<u64, &Idea> = theirs.iter().map(|i| (i.id, i)).collect();
+
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut seen = HashSet::new();
+
+    for idea in ours {
+        seen.insert(idea.id);
+        let original = base.get(&idea.id).copied();
+        match (original, theirs_map.get(&idea.id)) {
+            // Unchanged here, so whatever is on disk is newer.
+            (Some(original), Some(their)) if original == idea => merged.push((*their).clone()),
+            // Deleted on disk and untouched here.
+            (Some(original), None) if original == idea => {}
+            // Edited here but deleted on disk.
+            (Some(_), None) => {
+                conflicts.push(Conflict {
+                    id: idea.id,
+                    ours: Some(idea.clone()),
+                    theirs: None,
+                    fields: Vec::new(),
+                });
+                merged.push(idea.clone());
+            }
+            (original, Some(their)) => {
+                let (idea, fields) = merge_idea(original, idea, their);
+                if !fields.is_empty() {
+                    let mut their_version = to_map(&idea);
+                    for field in &fields {
+                        their_version.insert(field.name.clone(), field.theirs.clone());
+                    }
+                    conflicts.push(Conflict {
+                        id: idea.id,
+                        ours: Some(idea.clone()),
+                        theirs: Some(from_map(their_version).unwrap_or_else(|| (*their).clone())),
+                        fields,
+                    });
+                }
+                merged.push(idea);
+            }
+            // Added here.
+            (None, None) => merged.push(idea.clone()),
+        }
+    }
+
+    for idea in theirs {
+        if seen.contains(&idea.id) {
+            continue;
+        }
+        match base.get(&idea.id) {
+            // Added on disk.
+            None => merged.push(idea.clone()),
+            // Deleted here but edited on disk: keep their edit and let the
+            // user decide.
+            Some(original) if *original != idea && !ours_map.contains_key(&idea.id) => {
+                conflicts.push(Conflict {
+                    id: idea.id,
+                    ours: None,
+                    theirs: Some(idea.clone()),
+                    fields: Vec::new(),
+                });
+                merged.push(idea.clone());
+            }
+            Some(_) => {}
+        }
+    }
+
+    (merged, conflicts)
+}
+
+mod tests {
+    #[test]
+    fn test_merge_keeps_both_sides() {
+        use crate::app::idea::Idea;
+        use crate::app::merge::merge;
+
+        let a = Idea::new("a", "");
+        let b = Idea::new("b", "");
+        let base = vec![a.clone(), b.clone()];
+
+        let mut ours = base.clone();
+        ours.push(Idea::new("ours", ""));
+        ours[0].description = String::from("edited here");
+
+        let mut theirs = base.clone();
+        theirs.remove(1);
+        theirs.push(Idea::new("theirs", ""));
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        let titles: Vec<&str> = merged.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["a", "ours", "theirs"]);
+        assert_eq!(merged[0].description, "edited here");
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        use crate::app::idea::Idea;
+        use crate::app::merge::merge;
+
+        let base = vec![Idea::new("a", "")];
+        let mut ours = base.clone();
+        ours[0].title = String::from("ours");
+        let mut theirs = base.clone();
+        theirs[0].title = String::from("theirs");
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert_eq!(merged[0].title, "ours");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].theirs.as_ref().unwrap().title, "theirs");
+    }
+
+    #[test]
+    fn test_merge_combines_field_edits() {
+        use crate::app::idea::Idea;
+        use crate::app::merge::merge;
+
+        let base = vec![Idea::new("a", "old")];
+        let mut ours = base.clone();
+        ours[0].title = String::from("new title");
+        ours[0].updated_at += 10;
+        let mut theirs = base.clone();
+        theirs[0].description = String::from("new description");
+        theirs[0].updated_at += 20;
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged[0].title, "new title");
+        assert_eq!(merged[0].description, "new description");
+        assert_eq!(merged[0].updated_at, theirs[0].updated_at);
+
+        theirs[0].title = String::from("their title");
+        let (_, conflicts) = merge(&base, &ours, &theirs);
+        assert_eq!(conflicts[0].fields.len(), 1);
+        assert_eq!(conflicts[0].fields[0].name, "title");
+        let resolved = conflicts[0].resolve(&[0]).unwrap();
+        assert_eq!(resolved.title, "their title");
+        assert_eq!(resolved.description, "new description");
+    }
+}
@@ -1,16 +1,63 @@
+mod board;
 mod buffer;
+pub mod cli;
+mod clipboard;
+mod config;
+mod crypto;
+mod dates;
+mod dedupe;
+mod detail;
 mod event;
+mod form;
+mod git;
+mod history;
+mod idea;
+mod keymap;
+mod links;
+mod merge;
+mod modal;
+mod outline;
+mod palette;
+mod query;
+mod regroup;
 mod render;
+mod review;
+mod search;
+mod select;
 mod state;
+mod stats;
+mod store;
+mod timeline;
 mod utils;
+mod views;
 
-use buffer::View;
+use config::Config;
+use dedupe::ProfileCache;
 use event::Handler;
+use form::IdeaForm;
+use git::{Git, Revision};
+use idea::{Idea, Status};
+use merge::Conflict;
+use modal::Modal;
+use outline::{Handler as OutlineHandler, Outline};
+use query::{Query, Sort};
 use render::Render;
-use std::{io::BufReader, path::PathBuf};
-use utils::hex_to_rgb;
+use search::{Handler as SearchHandler, SearchIndex, index_path};
+use state::Handler as StateHandler;
+use std::{
+    collections::HashSet,
+    io::ErrorKind,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use store::Store;
+use timeline::Group;
+use utils::{data_path, hex_to_rgb, today};
+use views::{SmartView, load_views};
 
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 
 use ratatui::{
     DefaultTerminal, Frame,
@@ -20,80 +67,140 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-struct Idea {
-    title: String,
-    description: String,
-}
-
-impl Idea {
-    fn new(title: &str, description: &str) -> Self {
-        Self {
-            title: String::from(title),
-            description: String::from(description),
-        }
-    }
+/// A message shown in the bottom border until it expires.
+struct StatusMessage {
+    text: String,
+    expires_at: Instant,
 }
 
 #[derive(PartialEq)]
 enum Mode {
     Read,
-    Write,
-    Edit,
-}
-
-#[derive(PartialEq)]
-enum Focus {
-    Title,
-    Description,
-    Add,
+    Form,
+    History,
+    Detail,
+    Timeline,
+    Review,
+    Stats,
+    Palette,
+    Search,
 }
 
-pub struct App<'a> {
+pub struct App {
     exit: bool,
     active_index: usize,
     ideas: Vec<Idea>,
-    focus: Option<Focus>,
     mode: Mode,
-    buffer: &'a mut [String; 2],
-    title_view: View,
-    description_view: View,
-    cursor_position: (u16, u16),
-    cursor_offset: (u16, u16),
-    path: PathBuf,
+    form: Option<IdeaForm>,
+    draft: Option<IdeaForm>,
+    store: Store,
+    conflicts: Vec<Conflict>,
     active_index_path: PathBuf,
+    modals: Vec<Modal>,
+    list_area: Rect,
+    list_rows: Vec<usize>,
+    list_offset: u16,
+    last_click: Option<(Instant, usize)>,
+    dirty: bool,
+    last_tick: Instant,
+    status: Option<StatusMessage>,
+    pending_save: Option<Instant>,
+    config_path: PathBuf,
+    git: Option<Git>,
+    history: Vec<(Revision, Idea)>,
+    history_index: usize,
+    locked: bool,
+    collapsed: HashSet<u64>,
+    detail_item: usize,
+    timeline_index: usize,
+    config: Config,
+    show_archived: bool,
+    board: bool,
+    selecting: bool,
+    selected: HashSet<u64>,
+    select_anchor: Option<u64>,
+    review_queue: Vec<u64>,
+    /// The day due ideas were last announced, to announce them again once
+    /// the date changes.
+    announced_on: Option<chrono::NaiveDate>,
+    /// A new idea held back because it looks like the existing one with
+    /// this id.
+    pending_duplicate: Option<(Idea, u64)>,
+    profiles: ProfileCache,
+    /// The query the list is filtered with, as typed.
+    filter: Option<(String, Query)>,
+    /// The order the list is shown in, when not the stored one.
+    sort: Option<Sort>,
+    views: Vec<SmartView>,
+    views_path: PathBuf,
+    palette_input: String,
+    palette_index: usize,
+    search: SearchIndex,
+    search_input: String,
+    search_index: usize,
+    /// Whether the search index changed since it was written.
+    search_dirty: bool,
+    /// The text copied last, pasted with Ctrl-V in the idea popup.
+    clipboard: Option<String>,
 }
 
 #[allow(dead_code)]
-impl<'a> App<'a> {
-    pub fn new(buf: &'a mut [String; 2]) -> Self {
-        let mut path = std::env::current_exe().unwrap();
-        path.pop();
-        path.pop();
-        path.pop();
-        path.push("ideas.json");
+impl App {
+    pub fn new() -> Self {
+        let path = data_path("ideas.json");
         println!("{:?}", path);
 
-        let mut path2 = std::env::current_exe().unwrap();
-        path2.pop();
-        path2.pop();
-        path2.pop();
-        path2.push("index.txt");
+        let path2 = data_path("index.txt");
         println!("{:?}", path2);
 
         Self {
-            path: path,
+            store: Store::new(path),
+            conflicts: Vec::new(),
             active_index_path: path2,
             mode: Mode::Read,
             exit: false,
             active_index: 0,
-            focus: None,
             ideas: Vec::new(),
-            title_view: View::new(),
-            description_view: View::new(),
-            buffer: buf,
-            cursor_position: (0, 0),
-            cursor_offset: (0, 0),
+            form: None,
+            draft: None,
+            modals: Vec::new(),
+            list_area: Rect::default(),
+            list_rows: Vec::new(),
+            list_offset: 0,
+            last_click: None,
+            dirty: true,
+            last_tick: Instant::now(),
+            status: None,
+            pending_save: None,
+            config_path: data_path("ideas.config.json"),
+            git: None,
+            history: Vec::new(),
+            history_index: 0,
+            locked: false,
+            collapsed: HashSet::new(),
+            detail_item: 0,
+            timeline_index: 0,
+            config: Config::default(),
+            show_archived: false,
+            board: false,
+            selecting: false,
+            selected: HashSet::new(),
+            select_anchor: None,
+            review_queue: Vec::new(),
+            announced_on: None,
+            pending_duplicate: None,
+            profiles: ProfileCache::default(),
+            filter: None,
+            sort: None,
+            views: Vec::new(),
+            views_path: data_path("views.json"),
+            palette_input: String::new(),
+            palette_index: 0,
+            search: SearchIndex::default(),
+            search_input: String::new(),
+            search_index: 0,
+            search_dirty: false,
+            clipboard: None,
         }
     }
 
@@ -102,22 +209,88 @@ impl<'a> App<'a> {
         Color::Rgb(r, g, b)
     }
 
-    fn get_ideas_widget(&self) -> Vec<Line> {
+    /// Lines of the idea list, along with the index of the idea each line
+    /// belongs to. Sub-ideas are indented under their parent, which shows its
+    /// rolled up status and how many of its sub-ideas are done.
+    fn get_ideas_widget(&self) -> (Vec<Line<'_>>, Vec<usize>) {
         let mut lines: Vec<Line>;
         lines = Vec::new();
+        let mut rows = Vec::new();
+
+        let outline = Outline::new(&self.ideas);
+        let statuses = outline.statuses(&self.ideas);
+        let today = today();
+        for (i, depth) in self.outline_rows() {
+            let idea = &self.ideas[i];
+            let children = outline.children(idea.id);
+            let marker = match (children.is_empty(), self.collapsed.contains(&idea.id)) {
+                (true, _) => "",
+                (false, true) => "▸ ",
+                (false, false) => "▾ ",
+            };
+            let mut indent = "  ".repeat(depth);
+            if self.selecting {
+                let mark = if self.selected.contains(&idea.id) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                indent.insert_str(0, mark);
+            }
+            let progress = match idea.progress() {
+                Some((done, total)) => format!(" [{}/{}]", done, total),
+                None => String::new(),
+            };
+
+            let mut details = Vec::new();
+            if idea.archived {
+                details.push(String::from("archived"));
+            }
+            if statuses[i] != Status::Inbox {
+                details.push(statuses[i].label().to_string());
+            }
+            details.extend(idea.tags.iter().map(|t| format!("#{}", t)));
+            if statuses[i] != Status::Done {
+                if let Some(due) = idea.due {
+                    details.push(match Group::of(due, today) {
+                        Group::Overdue => String::from("overdue"),
+                        Group::Today => String::from("due today"),
+                        _ => format!("due {}", due.format("%b %-d")),
+                    });
+                }
+                if idea.review.is_some_and(|r| Group::of(r, today).is_due()) {
+                    details.push(String::from("review"));
+                }
+            }
+            if !children.is_empty() {
+                let done = children
+                    .iter()
+                    .filter(|c| statuses[**c] == Status::Done)
+                    .count();
+                details.push(format!("{}/{}", done, children.len()));
+            }
+            let details = if details.is_empty() {
+                String::new()
+            } else {
+                format!("  [{}]", details.join(" "))
+            };
+            let details = Span::styled(details, Style::default().fg(Color::DarkGray));
 
-        for (i, idea) in self.ideas.iter().enumerate() {
             if i == self.active_index {
                 lines.push("".into());
                 let mut title_string = String::new();
                 title_string.push_str("> ");
+                title_string.push_str(&indent);
+                title_string.push_str(marker);
                 title_string.push_str(&idea.title);
+                title_string.push_str(&progress);
                 let title = Span::styled(title_string, Style::default().fg(Color::Green));
-                lines.push(title.into());
+                lines.push(Line::from(vec![title, details]));
 
-                if idea.description != "" {
+                if !idea.description.is_empty() {
                     let mut desc_string = String::new();
                     desc_string.push_str("      ");
+                    desc_string.push_str(&indent);
                     desc_string.push_str(&idea.description);
                     let desc = Span::styled(desc_string, Style::default().fg(Color::Gray));
                     lines.push(desc.into());
@@ -126,55 +299,72 @@ impl<'a> App<'a> {
                 lines.push("".into());
             } else {
                 let mut title_string = String::new();
+                title_string.push_str(&indent);
+                title_string.push_str(marker);
                 title_string.push_str(&idea.title);
+                title_string.push_str(&progress);
                 let title = Span::styled(title_string, Style::default().fg(Color::White));
-                lines.push(title.into());
+                lines.push(Line::from(vec![title, details]));
             }
+            rows.resize(lines.len(), i);
+        }
+        (lines, rows)
+    }
+
+    /// Mentions ideas whose due or review date has come, so they resurface.
+    /// Runs on the first tick with the ideas loaded and again every new day.
+    fn announce_due(&mut self) {
+        let today = today();
+        if self.announced_on == Some(today) {
+            return;
+        }
+        self.announced_on = Some(today);
+        let due: HashSet<usize> = timeline::entries(&self.ideas, today)
+            .into_iter()
+            .filter(|e| e.group.is_due())
+            .map(|e| e.index)
+            .collect();
+        if !due.is_empty() {
+            self.set_status(&format!(
+                "{} ideas need attention, t for the timeline",
+                due.len()
+            ));
         }
-        lines
     }
 
     pub fn run(&mut self, mut terminal: DefaultTerminal) -> std::io::Result<()> {
-        let file = std::fs::File::open(&self.path)?;
-        let reader = BufReader::new(file);
-        let ideas: Vec<Idea> = serde_json::from_reader(reader).unwrap();
-        self.ideas = ideas;
+        match self.store.load() {
+            Ok(ideas) => self.ideas = ideas,
+            Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+                self.ask_passphrase(&err.to_string())
+            }
+            Err(err) => return Err(err),
+        }
+
+        self.config = Config::load(&self.config_path)?;
+        self.views = load_views(&self.views_path)?;
+        self.search = SearchIndex::load(&index_path(self.store.path()));
+        self.sync_search();
+        if self.config.git.enabled {
+            let git = Git::new(self.store.path());
+            match git.check_plain() {
+                Ok(()) => {
+                    git.init()?;
+                    self.git = Some(git);
+                }
+                Err(err) => self.set_status(&err.to_string()),
+            }
+        }
 
         let istring = std::fs::read_to_string(&self.active_index_path)?;
         let active_index: usize = istring.trim().parse().unwrap();
         self.active_index = active_index;
 
         while !self.exit {
-            terminal.draw(|frame| {
-                let area = frame.area();
-
-                let title = Span::styled("  Ideas  ", Style::default().fg(Color::Green));
-                let block = Block::default()
-                    .title(title)
-                    .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::DarkGray));
-                frame.render_widget(block, area);
-
-                let [_, body_area] = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Max(2), Constraint::Min(1)])
-                    .areas(area);
-
-                let ideas = self.get_ideas_widget();
-                let para = Paragraph::new(ideas).style(Style::default());
-
-                frame.render_widget(para, body_area);
-
-                match self.mode {
-                    Mode::Write => {
-                        self.render_write_mode(frame, area);
-                    }
-                    Mode::Edit => {
-                        self.render_edit_mode(frame, area);
-                    }
-                    _ => {}
-                }
-            })?;
+            if self.dirty {
+                terminal.draw(|frame| self.draw(frame))?;
+                self.dirty = false;
+            }
             self.handle_events()?;
         }
         Ok(())
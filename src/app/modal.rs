@@ -0,0 +1,363 @@
+use ratatui::widgets::{Clear, Wrap};
+
+use super::clipboard::Handler as ClipboardHandler;
+use super::dedupe::Handler as DedupeHandler;
+use super::idea::Status;
+use super::regroup::Handler as RegroupHandler;
+use super::select::Handler as SelectHandler;
+use super::state::Handler as StateHandler;
+use super::views::Handler as ViewsHandler;
+use super::{
+    App, Block, Borders, Color, Frame, KeyCode, KeyEvent, Line, Paragraph, Rect, Span, Style,
+};
+use crate::app::utils::popup_rect;
+
+/// What the app should do once a modal is answered.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Copy,
+    /// Deletes the idea with this id, the one the modal was opened for.
+    DeleteIdea(u64),
+    DeleteSelected,
+    Duplicate,
+    Filter,
+    MergeSelected,
+    SplitIdea,
+    TagSelected,
+    UntagSelected,
+    SetSelectedStatus,
+    ExportSelected,
+    Quit,
+    ResolveConflict,
+    Unlock,
+}
+
+#[allow(dead_code)]
+pub enum Kind {
+    Confirm {
+        yes: bool,
+    },
+    Prompt {
+        input: String,
+        secret: bool,
+    },
+    Select {
+        options: Vec<String>,
+        selected: usize,
+        checked: Option<Vec<bool>>,
+    },
+    Message,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Response {
+    Yes,
+    No,
+    Text(String),
+    Selected(Vec<usize>),
+    Dismissed,
+}
+
+pub struct Modal {
+    title: String,
+    message: String,
+    kind: Kind,
+    action: Option<Action>,
+}
+
+#[allow(dead_code)]
+impl Modal {
+    pub fn confirm(title: &str, message: &str, action: Action) -> Self {
+        Self::new(title, message, Kind::Confirm { yes: false }, Some(action))
+    }
+
+    pub fn prompt(title: &str, message: &str, initial: &str, action: Action) -> Self {
+        let kind = Kind::Prompt {
+            input: initial.to_string(),
+            secret: false,
+        };
+        Self::new(title, message, kind, Some(action))
+    }
+
+    /// A prompt that doesn't show what is typed, for passphrases.
+    pub fn secret(title: &str, message: &str, action: Action) -> Self {
+        let kind = Kind::Prompt {
+            input: String::new(),
+            secret: true,
+        };
+        Self::new(title, message, kind, Some(action))
+    }
+
+    pub fn select(title: &str, message: &str, options: Vec<String>, action: Action) -> Self {
+        let kind = Kind::Select {
+            options,
+            selected: 0,
+            checked: None,
+        };
+        Self::new(title, message, kind, Some(action))
+    }
+
+    pub fn multi_select(title: &str, message: &str, options: Vec<String>, action: Action) -> Self {
+        let checked = Some(vec![false; options.len()]);
+        let kind = Kind::Select {
+            options,
+            selected: 0,
+            checked,
+        };
+        Self::new(title, message, kind, Some(action))
+    }
+
+    pub fn message(title: &str, message: &str) -> Self {
+        Self::new(title, message, Kind::Message, None)
+    }
+
+    fn new(title: &str, message: &str, kind: Kind, action: Option<Action>) -> Self {
+        Self {
+            title: title.to_string(),
+            message: message.to_string(),
+            kind,
+            action,
+        }
+    }
+
+    /// Adds pasted text to a prompt, on one line.
+    pub fn paste(&mut self, text: &str) {
+        if let Kind::Prompt { input, .. } = &mut self.kind {
+            let text = text.trim_end_matches(['\r', '\n']);
+            input.extend(
+                text.chars()
+                    .map(|c| if c == '\n' || c == '\r' { ' ' } else { c }),
+            );
+        }
+    }
+
+    /// Feeds a key to the modal. Returns `Some` once the modal has been answered
+    /// and should be closed.
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> Option<Response> {
+        match &mut self.kind {
+            Kind::Confirm { yes } => match key_event.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => Some(Response::Yes),
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Some(Response::No),
+                KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Tab
+                | KeyCode::Char('h')
+                | KeyCode::Char('l') => {
+                    *yes = !*yes;
+                    None
+                }
+                KeyCode::Enter => Some(if *yes { Response::Yes } else { Response::No }),
+                _ => None,
+            },
+            Kind::Prompt { input, .. } => match key_event.code {
+                KeyCode::Char(char) => {
+                    input.push(char);
+                    None
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    None
+                }
+                KeyCode::Enter => Some(Response::Text(input.clone())),
+                KeyCode::Esc => Some(Response::Dismissed),
+                _ => None,
+            },
+            Kind::Select {
+                options,
+                selected,
+                checked,
+            } => match key_event.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    if *selected + 1 < options.len() {
+                        *selected += 1;
+                    }
+                    None
+                }
+                KeyCode::Char(' ') => {
+                    if let Some(checked) = checked
+                        && let Some(c) = checked.get_mut(*selected)
+                    {
+                        *c = !*c;
+                    }
+                    None
+                }
+                KeyCode::Enter => match checked {
+                    Some(checked) => Some(Response::Selected(
+                        (0..checked.len()).filter(|i| checked[*i]).collect(),
+                    )),
+                    None if options.is_empty() => Some(Response::Dismissed),
+                    None => Some(Response::Selected(vec![*selected])),
+                },
+                KeyCode::Esc => Some(Response::Dismissed),
+                _ => None,
+            },
+            Kind::Message => match key_event.code {
+                KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => Some(Response::Dismissed),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let rect = popup_rect(50, 40, (30, 7), (70, 16), area);
+
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+
+        let mut lines: Vec<Line> = Vec::new();
+        if !self.message.is_empty() {
+            lines.push(Line::styled(self.message.clone(), text_style));
+            lines.push("".into());
+        }
+
+        match &self.kind {
+            Kind::Confirm { yes } => {
+                let (yes_style, no_style) = if *yes {
+                    (active_style, passive_style)
+                } else {
+                    (passive_style, active_style)
+                };
+                lines.push(Line::from(vec![
+                    Span::styled("  [y] Yes  ", yes_style),
+                    Span::styled("  [n] No  ", no_style),
+                ]));
+            }
+            Kind::Prompt { input, secret } => {
+                let text = if *secret {
+                    "*".repeat(input.chars().count())
+                } else {
+                    input.clone()
+                };
+                lines.push(Line::styled(format!("> {}", text), active_style));
+            }
+            Kind::Select {
+                options,
+                selected,
+                checked,
+            } => {
+                for (i, option) in options.iter().enumerate() {
+                    let marker = match checked {
+                        Some(checked) if checked[i] => "[x] ",
+                        Some(_) => "[ ] ",
+                        None => "",
+                    };
+                    let (prefix, style) = if i == *selected {
+                        ("> ", active_style)
+                    } else {
+                        ("  ", text_style)
+                    };
+                    lines.push(Line::styled(
+                        format!("{}{}{}", prefix, marker, option),
+                        style,
+                    ));
+                }
+            }
+            Kind::Message => {
+                lines.push(Line::styled("  [Enter] OK", active_style));
+            }
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", self.title))
+            .style(passive_style);
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(Clear, rect);
+        frame.render_widget(paragraph, rect);
+    }
+}
+
+pub trait Handler {
+    fn open_modal(&mut self, modal: Modal);
+    fn handle_modal_key(&mut self, key_event: KeyEvent) -> std::io::Result<()>;
+    fn resolve_modal(&mut self, action: Action, response: Response) -> std::io::Result<()>;
+}
+
+impl Handler for App {
+    fn open_modal(&mut self, modal: Modal) {
+        self.modals.push(modal);
+    }
+
+    fn handle_modal_key(&mut self, key_event: KeyEvent) -> std::io::Result<()> {
+        let response = match self.modals.last_mut() {
+            Some(modal) => modal.handle_key(key_event),
+            None => return Ok(()),
+        };
+
+        if let Some(response) = response {
+            let modal = self.modals.pop().unwrap();
+            if let Some(action) = modal.action {
+                self.resolve_modal(action, response)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_modal(&mut self, action: Action, response: Response) -> std::io::Result<()> {
+        match (action, response) {
+            (Action::Copy, Response::Selected(selected)) => {
+                if let Some(choice) = selected.first() {
+                    self.copy_active(*choice);
+                }
+            }
+            (Action::DeleteIdea(id), Response::Yes) => {
+                // A reload while the modal was open may have moved or removed it.
+                let Some(index) = self.ideas.iter().position(|i| i.id == id) else {
+                    self.set_status("The idea is already gone");
+                    return Ok(());
+                };
+                self.remove_idea(index);
+                self.drop_from_review(id);
+                if index < self.active_index {
+                    self.active_index -= 1;
+                }
+                if self.active_index >= self.ideas.len() {
+                    self.active_index = self.ideas.len().saturating_sub(1);
+                }
+                self.schedule_save();
+                self.set_status("Idea deleted");
+            }
+            (Action::DeleteSelected, Response::Yes) => self.delete_selected(),
+            (Action::Duplicate, Response::Selected(selected)) => {
+                self.resolve_duplicate(selected.first().copied());
+            }
+            (Action::Duplicate, _) => self.resolve_duplicate(None),
+            (Action::Filter, Response::Text(text)) => self.set_filter(&text),
+            (Action::MergeSelected, Response::Yes) => self.merge_selected(),
+            (Action::SplitIdea, Response::Yes) => self.split_idea(),
+            (Action::TagSelected, Response::Text(text)) => self.tag_selected(&text, true),
+            (Action::UntagSelected, Response::Text(text)) => self.tag_selected(&text, false),
+            (Action::SetSelectedStatus, Response::Selected(selected)) => {
+                if let Some(status) = selected.first().and_then(|i| Status::ALL.get(*i)) {
+                    self.set_selected_status(*status);
+                }
+            }
+            (Action::ExportSelected, Response::Text(path)) => self.export_selected(&path),
+            (Action::Quit, Response::Yes) => {
+                self.quit()?;
+            }
+            (Action::ResolveConflict, Response::Selected(selected)) => {
+                self.resolve_conflict(&selected);
+            }
+            (Action::ResolveConflict, _) => {
+                self.resolve_conflict(&[]);
+            }
+            (Action::Unlock, Response::Text(passphrase)) => {
+                self.unlock(&passphrase);
+            }
+            (Action::Unlock, _) => {
+                self.quit()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+
+use super::App;
+use super::idea::{Idea, Status};
+use super::query::Sort;
+use super::state::Handler as StateHandler;
+
+/// The idea list seen as a tree through each idea's `parent`. Siblings keep
+/// the order they have in the list unless sorted; ideas whose parent is
+/// missing, e.g. deleted elsewhere, are shown at the top level.
+pub struct Outline {
+    roots: Vec<usize>,
+    children: HashMap<u64, Vec<usize>>,
+}
+
+impl Outline {
+    pub fn new(ideas: &[Idea]) -> Self {
+        let ids: HashSet<u64> = ideas.iter().map(|i| i.id).collect();
+        let mut roots = Vec::new();
+        let mut children: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, idea) in ideas.iter().enumerate() {
+            match idea.parent {
+                Some(parent) if parent != idea.id && ids.contains(&parent) => {
+                    children.entry(parent).or_default().push(index)
+                }
+                _ => roots.push(index),
+            }
+        }
+        Self { roots, children }
+    }
+
+    pub fn children(&self, id: u64) -> &[usize] {
+        self.children
+            .get(&id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Indices of the ideas sharing a parent with the idea at `index`.
+    pub fn siblings(&self, ideas: &[Idea], index: usize) -> &[usize] {
+        match ideas[index].parent {
+            Some(parent) if !self.roots.contains(&index) => self.children(parent),
+            _ => &self.roots,
+        }
+    }
+
+    /// Orders the ideas under each parent, and the top level, by `sort`.
+    pub fn sort(&mut self, ideas: &[Idea], sort: &Sort) {
+        sort.order(ideas, &mut self.roots);
+        for children in self.children.values_mut() {
+            sort.order(ideas, children);
+        }
+    }
+
+    /// Every idea in outline order with its depth, leaving out the sub-ideas
+    /// of collapsed ones.
+    pub fn rows(&self, ideas: &[Idea], collapsed: &HashSet<u64>) -> Vec<(usize, usize)> {
+        let mut rows = Vec::new();
+        let mut visited = vec![false; ideas.len()];
+        for root in &self.roots {
+            self.walk(ideas, collapsed, *root, 0, &mut visited, &mut rows);
+        }
+        // Ideas in a parent cycle are never reached from the top level.
+        for index in 0..ideas.len() {
+            if !visited[index] {
+                self.walk(ideas, collapsed, index, 0, &mut visited, &mut rows);
+            }
+        }
+        rows
+    }
+
+    fn walk(
+        &self,
+        ideas: &[Idea],
+        collapsed: &HashSet<u64>,
+        index: usize,
+        depth: usize,
+        visited: &mut Vec<bool>,
+        rows: &mut Vec<(usize, usize)>,
+    ) {
+        if visited[index] {
+            return;
+        }
+        visited[index] = true;
+        rows.push((index, depth));
+        if collapsed.contains(&ideas[index].id) {
+            self.mark_visited(ideas, index, visited);
+            return;
+        }
+        for child in self.children(ideas[index].id) {
+            self.walk(ideas, collapsed, *child, depth + 1, visited, rows);
+        }
+    }
+
+    fn mark_visited(&self, ideas: &[Idea], index: usize, visited: &mut Vec<bool>) {
+        for child in self.children(ideas[index].id) {
+            if !visited[*child] {
+                visited[*child] = true;
+                self.mark_visited(ideas, *child, visited);
+            }
+        }
+    }
+
+    /// The status of every idea with its sub-ideas rolled up: an idea whose
+    /// sub-ideas are all done is done, one with an active sub-idea is active,
+    /// otherwise it keeps its own status.
+    pub fn statuses(&self, ideas: &[Idea]) -> Vec<Status> {
+        let mut statuses: Vec<Option<Status>> = vec![None; ideas.len()];
+        for (index, _) in self.rows(ideas, &HashSet::new()).into_iter().rev() {
+            let children: Vec<Status> = self
+                .children(ideas[index].id)
+                .iter()
+                .filter_map(|c| statuses[*c])
+                .collect();
+            let own = ideas[index].status;
+            statuses[index] = Some(if children.is_empty() {
+                own
+            } else if children.iter().all(|s| *s == Status::Done) {
+                Status::Done
+            } else if children.contains(&Status::Active) {
+                Status::Active
+            } else {
+                own
+            });
+        }
+        statuses
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect()
+    }
+}
+
+/// Drops the rows for which `visible` is false along with everything below
+/// them.
+pub fn filter_rows(
+    rows: Vec<(usize, usize)>,
+    visible: impl Fn(usize) -> bool,
+) -> Vec<(usize, usize)> {
+    let mut hidden_depth = None;
+    rows.into_iter()
+        .filter(|(index, depth)| {
+            if hidden_depth.is_some_and(|hidden| *depth > hidden) {
+                return false;
+            }
+            hidden_depth = None;
+            if visible(*index) {
+                true
+            } else {
+                hidden_depth = Some(*depth);
+                false
+            }
+        })
+        .collect()
+}
+
+impl App {
+    /// The outline in the order the list is shown in.
+    fn shown_outline(&self) -> Outline {
+        let mut outline = Outline::new(&self.ideas);
+        if let Some(sort) = &self.sort {
+            outline.sort(&self.ideas, sort);
+        }
+        outline
+    }
+}
+
+pub trait Handler {
+    fn outline_rows(&self) -> Vec<(usize, usize)>;
+    fn select_next(&mut self);
+    fn select_previous(&mut self);
+    fn expand(&mut self);
+    fn collapse(&mut self);
+    fn toggle_collapsed(&mut self);
+    fn reveal(&mut self, index: usize);
+    fn indent(&mut self);
+    fn outdent(&mut self);
+}
+
+impl Handler for App {
+    /// The rows of the list in the order it's sorted by: the outline without
+    /// collapsed sub-ideas, ideas the filter leaves out and, unless they're
+    /// shown, archived ideas.
+    fn outline_rows(&self) -> Vec<(usize, usize)> {
+        let rows = self.shown_outline().rows(&self.ideas, &self.collapsed);
+        let filtered = self.filtered();
+        filter_rows(rows, |i| {
+            (self.show_archived || !self.ideas[i].archived)
+                && filtered.as_ref().is_none_or(|shown| shown.contains(&i))
+        })
+    }
+
+    fn select_next(&mut self) {
+        let rows = self.outline_rows();
+        let position = rows.iter().position(|(i, _)| *i == self.active_index);
+        if let Some((index, _)) = rows.get(position.map_or(0, |p| p + 1)) {
+            self.active_index = *index;
+        }
+    }
+
+    fn select_previous(&mut self) {
+        let rows = self.outline_rows();
+        let position = rows.iter().position(|(i, _)| *i == self.active_index);
+        if let Some((index, _)) = rows.get(position.unwrap_or(0).saturating_sub(1)) {
+            self.active_index = *index;
+        }
+    }
+
+    /// Shows the sub-ideas of the selected idea, or moves to the first one
+    /// when they're already shown.
+    fn expand(&mut self) {
+        let Some(id) = self.ideas.get(self.active_index).map(|i| i.id) else {
+            return;
+        };
+        let outline = Outline::new(&self.ideas);
+        if let Some(first) = outline.children(id).first()
+            && !self.collapsed.remove(&id)
+        {
+            self.active_index = *first;
+        }
+    }
+
+    /// Hides the sub-ideas of the selected idea, or moves to its parent when
+    /// there are none to hide.
+    fn collapse(&mut self) {
+        let Some(idea) = self.ideas.get(self.active_index) else {
+            return;
+        };
+        let outline = Outline::new(&self.ideas);
+        if !outline.children(idea.id).is_empty() && !self.collapsed.contains(&idea.id) {
+            self.collapsed.insert(idea.id);
+        } else if let Some(parent) = idea.parent
+            && let Some(index) = self.ideas.iter().position(|i| i.id == parent)
+        {
+            self.active_index = index;
+        }
+    }
+
+    fn toggle_collapsed(&mut self) {
+        let Some(id) = self.ideas.get(self.active_index).map(|i| i.id) else {
+            return;
+        };
+        if Outline::new(&self.ideas).children(id).is_empty() {
+            return;
+        }
+        if !self.collapsed.remove(&id) {
+            self.collapsed.insert(id);
+        }
+    }
+
+    /// Expands the parents of the idea at `index` so it shows in the list.
+    fn reveal(&mut self, index: usize) {
+        let mut parent = self.ideas.get(index).and_then(|i| i.parent);
+        let mut depth = 0;
+        while let Some(id) = parent
+            && depth < self.ideas.len()
+        {
+            self.collapsed.remove(&id);
+            parent = self
+                .ideas
+                .iter()
+                .find(|i| i.id == id)
+                .and_then(|i| i.parent);
+            depth += 1;
+        }
+    }
+
+    /// Makes the selected idea the last sub-idea of the one shown above it.
+    fn indent(&mut self) {
+        let index = self.active_index;
+        if index >= self.ideas.len() {
+            return;
+        }
+        let outline = self.shown_outline();
+        let siblings = outline.siblings(&self.ideas, index);
+        let position = siblings.iter().position(|i| *i == index).unwrap_or(0);
+        if position == 0 {
+            self.set_status("Nothing above to indent under");
+            return;
+        }
+        let parent = siblings[position - 1];
+        let parent_id = self.ideas[parent].id;
+        let last_child = outline.children(parent_id).iter().max().copied();
+
+        let mut idea = self.ideas.remove(index);
+        idea.parent = Some(parent_id);
+        idea.touch();
+        // Keep it after the existing sub-ideas, which are ordered by position.
+        let target = match last_child {
+            Some(last) if last > index => last,
+            _ => index,
+        };
+        self.ideas.insert(target, idea);
+        self.active_index = target;
+        self.collapsed.remove(&parent_id);
+        self.schedule_save();
+    }
+
+    /// Moves the selected idea up a level, right after its old parent.
+    fn outdent(&mut self) {
+        let index = self.active_index;
+        let Some(parent_id) = self.ideas.get(index).and_then(|i| i.parent) else {
+            return;
+        };
+        let Some(parent) = self.ideas.iter().position(|i| i.id == parent_id) else {
+            return;
+        };
+
+        let mut idea = self.ideas.remove(index);
+        idea.parent = self
+            .ideas
+            .iter()
+            .find(|i| i.id == parent_id)
+            .and_then(|p| p.parent);
+        idea.touch();
+        let parent = if parent > index { parent - 1 } else { parent };
+        self.ideas.insert(parent + 1, idea);
+        self.active_index = parent + 1;
+        self.schedule_save();
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_outline_rows_and_roll_up() {
+        use crate::app::idea::{Idea, Status};
+        use crate::app::outline::Outline;
+        use crate::app::query::Sort;
+        use std::collections::HashSet;
+
+        let parent = Idea::new("parent", "");
+        let mut first = Idea::new("first", "");
+        first.parent = Some(parent.id);
+        first.status = Status::Done;
+        let mut second = Idea::new("second", "");
+        second.parent = Some(parent.id);
+        second.status = Status::Done;
+        let other = Idea::new("other", "");
+        let ideas = vec![first, other, parent.clone(), second];
+
+        let outline = Outline::new(&ideas);
+        let rows = outline.rows(&ideas, &HashSet::new());
+        assert_eq!(rows, vec![(1, 0), (2, 0), (0, 1), (3, 1)]);
+        assert_eq!(outline.statuses(&ideas)[2], Status::Done);
+
+        let collapsed = HashSet::from([parent.id]);
+        assert_eq!(outline.rows(&ideas, &collapsed), vec![(1, 0), (2, 0)]);
+
+        let mut sorted = Outline::new(&ideas);
+        sorted.sort(&ideas, &Sort::new(&ideas, "title", true).unwrap());
+        let rows = sorted.rows(&ideas, &HashSet::new());
+        assert_eq!(rows, vec![(2, 0), (3, 1), (0, 1), (1, 0)]);
+        assert_eq!(sorted.siblings(&ideas, 1), &[2, 1]);
+        assert!(Sort::new(&ideas, "priority", false).is_err());
+    }
+}
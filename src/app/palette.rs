@@ -0,0 +1,342 @@
+use ratatui::widgets::Clear;
+
+use super::idea::{Status, tags_from_text};
+use super::keymap::{keys_for, palette_commands};
+use super::outline::Handler as OutlineHandler;
+use super::query::Sort;
+use super::select::Handler as SelectHandler;
+use super::state::Handler as StateHandler;
+use super::utils::{popup_rect, truncate};
+use super::views::Handler as ViewsHandler;
+use super::{
+    App, Block, Borders, Color, Frame, KeyCode, KeyEvent, KeyModifiers, Line, Mode, Paragraph,
+    Rect, Span, Style,
+};
+
+/// Commands typed with arguments, and how they're used.
+const COMMANDS: [(&str, &str); 7] = [
+    ("tag", "tag add|remove TAGS"),
+    ("status", "status NAME"),
+    ("sort", "sort FIELD [desc] or sort none"),
+    ("filter", "filter QUERY"),
+    ("view", "view NAME"),
+    ("save", "save NAME"),
+    ("unsave", "unsave NAME"),
+];
+
+/// A line in the palette.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Entry {
+    /// The input itself, run as a command.
+    Typed,
+    /// How a command is used; choosing it starts typing it.
+    Usage(usize),
+    Action(usize),
+    Idea(usize),
+}
+
+/// How well `query` matches `text`, or `None` when its characters don't
+/// all appear in order. Runs of characters and word starts score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut last = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|c| *c == wanted)?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - position).min(3) as i64;
+        last = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+impl App {
+    /// What the palette lists for its input, best match first.
+    fn palette_entries(&self) -> Vec<Entry> {
+        let input = self.palette_input.trim_start();
+        let mut entries = Vec::new();
+        if let Some((command, _)) = input.split_once(' ') {
+            if COMMANDS.iter().any(|(name, _)| *name == command) {
+                entries.push(Entry::Typed);
+            }
+        } else {
+            entries.extend(
+                (0..COMMANDS.len())
+                    .filter(|i| COMMANDS[*i].0.starts_with(input))
+                    .map(Entry::Usage),
+            );
+        }
+
+        let actions = palette_commands()
+            .into_iter()
+            .enumerate()
+            .map(|(i, command)| (Entry::Action(i), fuzzy_score(input, command.name())));
+        let ideas = self
+            .ideas
+            .iter()
+            .enumerate()
+            .map(|(i, idea)| (Entry::Idea(i), fuzzy_score(input, &idea.title)));
+        let mut matches: Vec<(Entry, i64)> = actions
+            .chain(ideas)
+            .filter_map(|(entry, score)| Some((entry, score?)))
+            .collect();
+        matches.sort_by_key(|(_, score)| -score);
+        entries.extend(matches.into_iter().map(|(entry, _)| entry));
+        entries
+    }
+
+    /// Runs a typed command like `tag add rust` or `sort due desc`.
+    fn run_typed(&mut self, line: &str) {
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+        match command {
+            "tag" => {
+                let (how, tags) = argument.split_once(' ').unwrap_or((argument, ""));
+                let add = match how {
+                    "add" => true,
+                    "remove" | "rm" => false,
+                    _ => {
+                        self.set_status("Use tag add TAGS or tag remove TAGS");
+                        return;
+                    }
+                };
+                self.tag_active(tags, add);
+            }
+            "status" => match Status::ALL
+                .iter()
+                .find(|s| s.label().eq_ignore_ascii_case(argument))
+            {
+                Some(status) => self.status_active(*status),
+                None => self.set_status(&format!("No status named {:?}", argument)),
+            },
+            "sort" => {
+                let (field, order) = argument.split_once(' ').unwrap_or((argument, ""));
+                let descending = matches!(order.trim(), "desc" | "descending");
+                self.sort(field, descending);
+            }
+            _ => {
+                if !self.run_command(line) {
+                    self.set_status(&format!("Unknown command {:?}", command));
+                }
+            }
+        }
+    }
+
+    /// Runs `run` on the selected ideas, or the active one when not
+    /// selecting.
+    fn with_selection(&mut self, run: impl FnOnce(&mut App)) {
+        if self.selecting && !self.selected.is_empty() {
+            run(self);
+            return;
+        }
+        let Some(id) = self.ideas.get(self.active_index).map(|i| i.id) else {
+            return;
+        };
+        self.selected.insert(id);
+        run(self);
+        self.selected.clear();
+    }
+
+    fn tag_active(&mut self, tags: &str, add: bool) {
+        if tags_from_text(tags).is_empty() {
+            self.set_status("Name the tags, e.g. tag add rust");
+            return;
+        }
+        self.with_selection(|app| app.tag_selected(tags, add));
+    }
+
+    fn status_active(&mut self, status: Status) {
+        self.with_selection(|app| app.set_selected_status(status));
+    }
+
+    /// Shows the list sorted by `field`, or in the stored order for `none`.
+    fn sort(&mut self, field: &str, descending: bool) {
+        if field.is_empty() {
+            self.set_status("Sort by what? e.g. sort due or sort priority desc");
+            return;
+        }
+        if field == "none" {
+            self.sort = None;
+            self.set_status("Showing the stored order");
+            return;
+        }
+        match Sort::new(&self.ideas, field, descending) {
+            Ok(sort) => {
+                self.sort = Some(sort);
+                self.set_status(&format!("Sorted by {}", field));
+            }
+            Err(err) => self.set_status(&format!("Sort: {}", err)),
+        }
+    }
+
+    /// Selects the idea at `index`, showing it even when the filter or
+    /// archive hid it.
+    pub fn go_to_idea(&mut self, index: usize) {
+        if self.ideas[index].archived {
+            self.show_archived = true;
+        }
+        if self.filtered().is_some_and(|shown| !shown.contains(&index)) {
+            self.set_filter("");
+        }
+        self.active_index = index;
+        self.reveal(index);
+    }
+}
+
+/// A `:` or Ctrl-P popup that finds actions and ideas by typing a few
+/// letters of their name, and runs commands with arguments.
+pub trait Handler {
+    fn open_palette(&mut self);
+    fn handle_palette_key(&mut self, key_event: KeyEvent) -> std::io::Result<()>;
+    fn render_palette(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl Handler for App {
+    fn open_palette(&mut self) {
+        self.palette_input.clear();
+        self.palette_index = 0;
+        self.mode = Mode::Palette;
+    }
+
+    fn handle_palette_key(&mut self, key_event: KeyEvent) -> std::io::Result<()> {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let count = self.palette_entries().len();
+        match key_event.code {
+            KeyCode::Esc => self.mode = Mode::Read,
+            KeyCode::Up => self.palette_index = self.palette_index.saturating_sub(1),
+            KeyCode::Char('p') if control => {
+                self.palette_index = self.palette_index.saturating_sub(1);
+            }
+            KeyCode::Down if self.palette_index + 1 < count => self.palette_index += 1,
+            KeyCode::Char('n') if control && self.palette_index + 1 < count => {
+                self.palette_index += 1;
+            }
+            KeyCode::Char('u') if control => {
+                self.palette_input.clear();
+                self.palette_index = 0;
+            }
+            KeyCode::Backspace => {
+                self.palette_input.pop();
+                self.palette_index = 0;
+            }
+            KeyCode::Char(c) if !control => {
+                self.palette_input.push(c);
+                self.palette_index = 0;
+            }
+            KeyCode::Enter => {
+                let input = self.palette_input.clone();
+                match self.palette_entries().get(self.palette_index).copied() {
+                    Some(Entry::Usage(i)) => {
+                        self.palette_input = format!("{} ", COMMANDS[i].0);
+                        self.palette_index = 0;
+                        return Ok(());
+                    }
+                    Some(Entry::Typed) => {
+                        self.mode = Mode::Read;
+                        self.run_typed(&input);
+                    }
+                    Some(Entry::Action(i)) => {
+                        self.mode = Mode::Read;
+                        self.perform(palette_commands()[i])?;
+                    }
+                    Some(Entry::Idea(i)) => {
+                        self.mode = Mode::Read;
+                        self.go_to_idea(i);
+                    }
+                    None if input.trim().is_empty() => self.mode = Mode::Read,
+                    None => {
+                        self.mode = Mode::Read;
+                        self.run_typed(&input);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn render_palette(&mut self, frame: &mut Frame, area: Rect) {
+        let rect = popup_rect(60, 60, (40, 13), (90, 30), area);
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Commands ")
+            .title_bottom(" ↑/↓ move  enter run  esc close ")
+            .style(passive_style);
+        let inner = block.inner(rect);
+        let width = inner.width.saturating_sub(2) as usize;
+        frame.render_widget(Clear, rect);
+        frame.render_widget(block, rect);
+
+        let prompt = Line::from(vec![
+            Span::styled(": ", active_style),
+            Span::styled(self.palette_input.clone(), text_style),
+        ]);
+        frame.render_widget(Paragraph::new(prompt), Rect { height: 1, ..inner });
+        frame.set_cursor_position((
+            inner.x + 2 + self.palette_input.chars().count() as u16,
+            inner.y,
+        ));
+
+        let list = Rect {
+            y: inner.y + 2,
+            height: inner.height.saturating_sub(2),
+            ..inner
+        };
+        let entries = self.palette_entries();
+        let commands = palette_commands();
+        let lines: Vec<Line> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let (prefix, style) = if i == self.palette_index {
+                    ("> ", active_style)
+                } else {
+                    ("  ", text_style)
+                };
+                let (name, hint) = match *entry {
+                    Entry::Typed => (self.palette_input.trim().to_string(), "run".into()),
+                    Entry::Usage(i) => (COMMANDS[i].1.to_string(), "command".into()),
+                    Entry::Action(i) => (commands[i].name().to_string(), keys_for(commands[i])),
+                    Entry::Idea(i) => (self.ideas[i].title.clone(), "idea".into()),
+                };
+                let hint_width = hint.chars().count();
+                let name = truncate(&name, width.saturating_sub(hint_width + 2));
+                let gap = width.saturating_sub(name.chars().count() + hint_width);
+                Line::from(vec![
+                    Span::styled(prefix, style),
+                    Span::styled(name, style),
+                    Span::styled(format!("{}{}", " ".repeat(gap), hint), passive_style),
+                ])
+            })
+            .collect();
+        let height = list.height.max(1);
+        let offset = (self.palette_index as u16).saturating_sub(height - 1);
+        frame.render_widget(Paragraph::new(lines).scroll((offset, 0)), list);
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_fuzzy_score() {
+        use crate::app::palette::fuzzy_score;
+
+        assert_eq!(fuzzy_score("", "Anything"), Some(0));
+        assert_eq!(fuzzy_score("xz", "New idea"), None);
+        assert!(fuzzy_score("ni", "New idea").is_some());
+        assert!(fuzzy_score("stat", "Stats") > fuzzy_score("stat", "Cycle status later"));
+        assert!(fuzzy_score("tl", "Timeline") > fuzzy_score("tl", "Split into sub-ideas"));
+    }
+}
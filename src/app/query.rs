@@ -0,0 +1,420 @@
+use std::cmp::Ordering;
+
+use chrono::NaiveDate;
+use serde_json::Value;
+
+use super::dates::{parse_date, parse_span, shift};
+use super::idea::{Idea, Status};
+use super::merge::display_value;
+use super::utils::today;
+
+/// How a field is compared with a value.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Test {
+    /// Words that must appear in the title or description.
+    Text(String),
+    Compare {
+        field: String,
+        op: Op,
+        value: String,
+    },
+    /// The field has a value.
+    Has(String),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Term {
+    negated: bool,
+    test: Test,
+}
+
+/// A parsed filter like `tag:rust status:active priority>=2 created:<30d
+/// "socket"`. Terms must all match; `or` separates alternatives and a
+/// leading `-` negates a term.
+///
+/// Fields are `tag`, `status`, `title`, `description`, `created`, `updated`,
+/// `due`, `review`, `archived`, `has` and the names of extra fields. Dates
+/// take anything `parse_date` reads or an amount like `30d` or `30days`,
+/// which is an age for `created` and `updated` and counts from today for
+/// `due` and `review`. A quoted term is always text to look for.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Query {
+    alternatives: Vec<Vec<Term>>,
+}
+
+const OPERATORS: [(&str, Op); 8] = [
+    (":>=", Op::Ge),
+    (":<=", Op::Le),
+    (":>", Op::Gt),
+    (":<", Op::Lt),
+    (">=", Op::Ge),
+    ("<=", Op::Le),
+    ("!=", Op::Ne),
+    (":", Op::Eq),
+];
+
+/// Single-character operators, checked after the longer ones.
+const SHORT_OPERATORS: [(char, Op); 3] = [('>', Op::Gt), ('<', Op::Lt), ('=', Op::Eq)];
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, String> {
+        let mut alternatives = vec![Vec::new()];
+        for (token, quoted) in tokenize(input)? {
+            if !quoted && token.eq_ignore_ascii_case("or") {
+                alternatives.push(Vec::new());
+                continue;
+            }
+            let term = parse_term(&token, quoted)?;
+            alternatives.last_mut().unwrap().push(term);
+        }
+        if alternatives.iter().any(Vec::is_empty) {
+            return Err(String::from("`or` needs terms on both sides"));
+        }
+        Ok(Query { alternatives })
+    }
+
+    /// Whether `idea` matches, with dates relative to `today`.
+    pub fn matches(&self, idea: &Idea, today: NaiveDate) -> bool {
+        self.alternatives.iter().any(|terms| {
+            terms
+                .iter()
+                .all(|term| term.negated != test(&term.test, idea, today))
+        })
+    }
+}
+
+/// Splits on spaces outside double quotes, dropping the quotes. Each token
+/// comes with whether it started with a quote, after an optional `-`.
+fn tokenize(input: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    let mut starts_quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                if !quoted && (current.is_empty() || current == "-") {
+                    starts_quoted = true;
+                }
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    tokens.push((std::mem::take(&mut current), starts_quoted));
+                    started = false;
+                    starts_quoted = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if quoted {
+        return Err(String::from("a quote isn't closed"));
+    }
+    if started {
+        tokens.push((current, starts_quoted));
+    }
+    Ok(tokens)
+}
+
+/// Reads one term. A `quoted` one is always text, whatever it contains.
+fn parse_term(token: &str, quoted: bool) -> Result<Term, String> {
+    let (negated, token) = match token.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+    if quoted {
+        let test = Test::Text(token.to_lowercase());
+        return Ok(Term { negated, test });
+    }
+    let field_end = token
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(token.len());
+    let (field, rest) = token.split_at(field_end);
+    let operator = OPERATORS
+        .iter()
+        .find(|(text, _)| rest.starts_with(text))
+        .map(|(text, op)| (text.len(), *op))
+        .or_else(|| {
+            SHORT_OPERATORS
+                .iter()
+                .find(|(c, _)| rest.starts_with(*c))
+                .map(|(_, op)| (1, *op))
+        });
+    let test = match operator {
+        Some((length, op)) if !field.is_empty() => {
+            let field = field.to_lowercase();
+            let value = rest[length..].to_string();
+            if value.is_empty() {
+                return Err(format!("{:?} needs a value", field));
+            }
+            match field.as_str() {
+                "has" => Test::Has(value.to_lowercase()),
+                "tag" | "status" | "archived" if !matches!(op, Op::Eq | Op::Ne) => {
+                    return Err(format!("{} can only be compared with : or !=", field));
+                }
+                "created" | "updated" | "due" | "review" => {
+                    if date_value(&field, &value, today()).is_none() {
+                        return Err(format!("can't read {:?} as a date", value));
+                    }
+                    Test::Compare { field, op, value }
+                }
+                _ => Test::Compare { field, op, value },
+            }
+        }
+        _ => Test::Text(token.to_lowercase()),
+    };
+    Ok(Term { negated, test })
+}
+
+/// The date a value stands for. Amounts like `30d` are that long ago for
+/// `created` and `updated`, and that far ahead otherwise.
+fn date_value(field: &str, value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let past = matches!(field, "created" | "updated");
+    match parse_span(value) {
+        Some((count, unit)) => shift(today, count, unit, !past),
+        None => parse_date(value, today).ok(),
+    }
+}
+
+fn local_date(secs: u64) -> Option<NaiveDate> {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|t| t.with_timezone(&chrono::Local).date_naive())
+}
+
+fn test(test: &Test, idea: &Idea, today: NaiveDate) -> bool {
+    match test {
+        Test::Text(text) => {
+            let haystack = format!("{}\n{}", idea.title, idea.description).to_lowercase();
+            haystack.contains(text.as_str())
+        }
+        Test::Has(field) => match field.as_str() {
+            "due" => idea.due.is_some(),
+            "review" => idea.review.is_some(),
+            "tags" | "tag" => !idea.tags.is_empty(),
+            "description" => !idea.description.trim().is_empty(),
+            "checklist" => !idea.checklist.is_empty(),
+            "parent" => idea.parent.is_some(),
+            field => idea.extra.get(field).is_some_and(|v| !v.is_null()),
+        },
+        Test::Compare { field, op, value } => compare(idea, field, *op, value, today),
+    }
+}
+
+fn compare(idea: &Idea, field: &str, op: Op, value: &str, today: NaiveDate) -> bool {
+    let text = |haystack: &str| {
+        let found = haystack.to_lowercase().contains(&value.to_lowercase());
+        found == (op != Op::Ne)
+    };
+    match field {
+        "tag" => {
+            let tag = value.trim_start_matches('#').to_lowercase();
+            idea.tags.contains(&tag) == (op == Op::Eq)
+        }
+        "status" => idea.status.label().eq_ignore_ascii_case(value) == (op == Op::Eq),
+        "archived" => {
+            let wanted = matches!(value.to_lowercase().as_str(), "yes" | "true" | "1");
+            (idea.archived == wanted) == (op == Op::Eq)
+        }
+        "title" => text(&idea.title),
+        "description" | "desc" => text(&idea.description),
+        "created" | "updated" | "due" | "review" => {
+            let date = match field {
+                "created" => local_date(idea.created_at),
+                "updated" => local_date(idea.updated_at),
+                "due" => idea.due,
+                _ => idea.review,
+            };
+            let (Some(date), Some(wanted)) = (date, date_value(field, value, today)) else {
+                return op == Op::Ne;
+            };
+            // An age turns the comparison around: created less than 30 days
+            // ago is a date after 30 days ago.
+            let past = matches!(field, "created" | "updated");
+            let amount = parse_span(value).is_some();
+            let ordering = date.cmp(&wanted);
+            op.accepts(if past && amount {
+                ordering.reverse()
+            } else {
+                ordering
+            })
+        }
+        field => match idea.extra.get(field) {
+            None | Some(Value::Null) => op == Op::Ne,
+            Some(Value::Number(number)) => match (number.as_f64(), value.parse::<f64>()) {
+                (Some(number), Ok(wanted)) => number
+                    .partial_cmp(&wanted)
+                    .is_some_and(|ordering| op.accepts(ordering)),
+                _ => op == Op::Ne,
+            },
+            Some(Value::String(string)) if matches!(op, Op::Eq | Op::Ne) => {
+                string.eq_ignore_ascii_case(value) == (op == Op::Eq)
+            }
+            Some(Value::String(string)) => {
+                op.accepts(string.to_lowercase().cmp(&value.to_lowercase()))
+            }
+            Some(other) => display_value(other).eq_ignore_ascii_case(value) == (op == Op::Eq),
+        },
+    }
+}
+
+/// What an idea is sorted by for `field`: a number or text, `None` when it
+/// has no value.
+fn sort_key(idea: &Idea, field: &str) -> Option<Value> {
+    match field {
+        "title" => Some(Value::from(idea.title.to_lowercase())),
+        "status" => Status::ALL
+            .iter()
+            .position(|s| *s == idea.status)
+            .map(Value::from),
+        "created" => Some(Value::from(idea.created_at)),
+        "updated" => Some(Value::from(idea.updated_at)),
+        "due" => idea.due.map(|d| Value::from(d.to_string())),
+        "review" => idea.review.map(|d| Value::from(d.to_string())),
+        field => match idea.extra.get(field)? {
+            Value::Null => None,
+            Value::String(string) => Some(Value::from(string.to_lowercase())),
+            value => Some(value.clone()),
+        },
+    }
+}
+
+fn compare_keys(a: &Value, b: &Value) -> Ordering {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        _ => display_value(a).cmp(&display_value(b)),
+    }
+}
+
+/// An order the list is shown in, by a field `Query` knows. It only changes
+/// how the list is shown; the stored order stays as it is.
+pub struct Sort {
+    pub field: String,
+    pub descending: bool,
+}
+
+impl Sort {
+    pub fn new(ideas: &[Idea], field: &str, descending: bool) -> Result<Self, String> {
+        let field = field.to_lowercase();
+        let known = matches!(
+            field.as_str(),
+            "title" | "status" | "created" | "updated" | "due" | "review"
+        );
+        if !known && !ideas.iter().any(|i| sort_key(i, &field).is_some()) {
+            return Err(format!("no idea has a value for {:?}", field));
+        }
+        Ok(Self { field, descending })
+    }
+
+    /// Sorts `indices` into `ideas`, keeping the order of ideas that compare
+    /// equal. Ideas without a value go last either way.
+    pub fn order(&self, ideas: &[Idea], indices: &mut [usize]) {
+        indices.sort_by_cached_key(|i| SortKey(sort_key(&ideas[*i], &self.field), self.descending));
+    }
+}
+
+/// A sort key ordering missing values last and the rest in either
+/// direction.
+#[derive(PartialEq, Eq)]
+struct SortKey(Option<Value>, bool);
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.0, &other.0) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) if self.1 => compare_keys(b, a),
+            (Some(a), Some(b)) => compare_keys(a, b),
+        }
+    }
+}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_query() {
+        use crate::app::idea::{Idea, Status};
+        use crate::app::query::Query;
+        use chrono::NaiveDate;
+
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let created = |days: i64| {
+            let date = today - chrono::Days::new(days as u64);
+            date.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp() as u64
+        };
+        let mut socket = Idea::new("Socket server", "Async rust");
+        socket.tags = vec!["rust".into()];
+        socket.status = Status::Active;
+        socket.created_at = created(10);
+        socket.extra.insert("priority".into(), 3.into());
+        let mut garden = Idea::new("Garden", "tomatoes");
+        garden.created_at = created(100);
+        garden.due = Some(today + chrono::Days::new(3));
+        let ideas = [socket, garden];
+
+        let matching = |query: &str| -> Vec<&str> {
+            let query = Query::parse(query).unwrap();
+            ideas
+                .iter()
+                .filter(|i| query.matches(i, today))
+                .map(|i| i.title.as_str())
+                .collect()
+        };
+        assert_eq!(
+            matching(r#"tag:rust status:active priority>=2 created:<30d "socket""#),
+            vec!["Socket server"]
+        );
+        assert_eq!(matching("created:>30d"), vec!["Garden"]);
+        assert_eq!(matching("-tag:rust"), vec!["Garden"]);
+        assert_eq!(matching("due:<1w or priority>5"), vec!["Garden"]);
+        assert_eq!(
+            matching("has:due or async"),
+            vec!["Socket server", "Garden"]
+        );
+        // Quoted, anything is text to look for.
+        assert_eq!(matching(r#""priority>=2""#), Vec::<&str>::new());
+        assert_eq!(
+            matching(r#"-"created:<soon""#),
+            vec!["Socket server", "Garden"]
+        );
+        assert_eq!(matching("created:<2weeks"), matching("created:<2w"));
+        assert!(Query::parse("tag>rust").is_err());
+        assert!(Query::parse("created:<soon").is_err());
+        assert!(Query::parse("due:<400000000y").is_err());
+        assert!(Query::parse("\"open").is_err());
+    }
+}
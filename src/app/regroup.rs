@@ -0,0 +1,206 @@
+use super::App;
+use super::idea::Idea;
+use super::links;
+use super::select::Handler as SelectHandler;
+use super::state::Handler as StateHandler;
+use super::utils::truncate;
+
+/// Longest title given to an idea split off from a description.
+const TITLE_LENGTH: usize = 80;
+
+/// The parts of a description that become ideas of their own: its bullets
+/// when it has several, otherwise its paragraphs. Also returns the text
+/// around a list, which stays with the idea that was split.
+pub fn split_description(text: &str) -> (Vec<String>, String) {
+    let mut bullets: Vec<String> = Vec::new();
+    let mut rest: Vec<&str> = Vec::new();
+    let mut in_bullet = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(item) = bullet(trimmed) {
+            bullets.push(item.to_string());
+            in_bullet = true;
+        } else if in_bullet && line.starts_with(char::is_whitespace) && !trimmed.is_empty() {
+            // An indented line continues the bullet above.
+            let last = bullets.last_mut().unwrap();
+            last.push('\n');
+            last.push_str(trimmed);
+        } else {
+            in_bullet = false;
+            // Keep a single blank line where the list was taken out.
+            if !(line.trim().is_empty() && rest.last().is_none_or(|l| l.trim().is_empty())) {
+                rest.push(line);
+            }
+        }
+    }
+    if bullets.len() >= 2 {
+        return (bullets, rest.join("\n").trim().to_string());
+    }
+
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+        } else {
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(line.trim());
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    (paragraphs, String::new())
+}
+
+/// The text of a bullet or numbered list line.
+fn bullet(line: &str) -> Option<&str> {
+    for marker in ["- ", "* ", "• "] {
+        if let Some(text) = line.strip_prefix(marker) {
+            return Some(text.trim());
+        }
+    }
+    let digits = line.find(|c: char| !c.is_ascii_digit())?;
+    let rest = line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "));
+    (digits > 0).then_some(rest?.trim())
+}
+
+/// An idea from one part of a split description: its first line is the
+/// title, the rest the description.
+fn idea_from_part(part: &str) -> Idea {
+    let (first, rest) = part.split_once('\n').unwrap_or((part, ""));
+    let mut idea = Idea::new(&truncate(first.trim(), TITLE_LENGTH), rest.trim());
+    if first.chars().count() > TITLE_LENGTH {
+        idea.description = format!("{}\n{}", first.trim(), rest.trim())
+            .trim()
+            .to_string();
+    }
+    idea
+}
+
+/// Turning several ideas into one and one into several.
+pub trait Handler {
+    fn merge_selected(&mut self);
+    fn split_idea(&mut self);
+}
+
+impl Handler for App {
+    /// Folds the selected ideas into the first of them. Their sub-ideas and
+    /// links move over to it.
+    fn merge_selected(&mut self) {
+        let mut indices =
+            (0..self.ideas.len()).filter(|i| self.selected.contains(&self.ideas[*i].id));
+        let Some(target) = indices.next() else {
+            return;
+        };
+        let others: Vec<Idea> = indices.map(|i| self.ideas[i].clone()).collect();
+        if others.is_empty() {
+            self.set_status("Select at least two ideas to merge");
+            return;
+        }
+
+        // Links without a stored id reach an idea by its title.
+        let found_by_title: Vec<bool> = others
+            .iter()
+            .map(|o| links::resolve(&self.ideas, &o.title).map(|i| self.ideas[i].id) == Some(o.id))
+            .collect();
+        let target_id = self.ideas[target].id;
+        for other in &others {
+            self.ideas[target].absorb(other);
+        }
+        let title = self.ideas[target].title.clone();
+        self.ideas.retain(|i| !others.iter().any(|o| o.id == i.id));
+        for idea in self.ideas.iter_mut() {
+            let mut changed = false;
+            if others.iter().any(|o| idea.parent == Some(o.id)) {
+                idea.parent = Some(target_id).filter(|id| *id != idea.id);
+                changed = true;
+            }
+            for (other, by_title) in others.iter().zip(&found_by_title) {
+                let old_title = by_title.then_some(other.title.as_str());
+                changed |= links::retarget(idea, other.id, old_title, target_id, &title);
+            }
+            if changed {
+                idea.touch();
+            }
+        }
+
+        self.active_index = self
+            .ideas
+            .iter()
+            .position(|i| i.id == target_id)
+            .unwrap_or(0);
+        self.stop_selecting();
+        self.schedule_save();
+        self.set_status(&format!(
+            "Merged {} ideas into \"{}\"",
+            others.len() + 1,
+            title
+        ));
+    }
+
+    /// Makes each bullet or paragraph of the selected idea's description a
+    /// sub-idea of it, taking over its status and tags. Text around a list
+    /// stays in its description.
+    fn split_idea(&mut self) {
+        let index = self.active_index;
+        let Some(idea) = self.ideas.get(index) else {
+            return;
+        };
+        let (parts, rest) = split_description(&idea.description);
+        if parts.len() < 2 {
+            self.set_status("Nothing to split, the description needs bullets or paragraphs");
+            return;
+        }
+
+        let (id, status, tags) = (idea.id, idea.status, idea.tags.clone());
+        let ids = idea.links.clone();
+        let count = parts.len();
+        for (offset, part) in parts.iter().enumerate() {
+            let mut new = idea_from_part(part);
+            new.parent = Some(id);
+            new.status = status;
+            new.tags = tags.clone();
+            // Links moved into the part keep pointing where they did.
+            new.links = ids.clone();
+            new.links = links::link_ids(&self.ideas, &new);
+            self.ideas.insert(index + 1 + offset, new);
+        }
+        self.ideas[index].description = rest;
+        self.ideas[index].links = links::link_ids(&self.ideas, &self.ideas[index]);
+        self.ideas[index].touch();
+        self.collapsed.remove(&id);
+        self.schedule_save();
+        self.set_status(&format!("Split into {} sub-ideas", count));
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_split_description() {
+        use crate::app::regroup::split_description;
+
+        let bullets = "Plan:\n- ship the board\n  with tests\n- write docs\n\n1. later";
+        assert_eq!(
+            split_description(bullets).0,
+            vec!["ship the board\nwith tests", "write docs", "later"]
+        );
+        assert_eq!(
+            split_description("First part.\n\nSecond\npart.").0,
+            vec!["First part.", "Second\npart."]
+        );
+        assert_eq!(split_description("- only one").0, vec!["- only one"]);
+
+        // Text around the list stays with the idea that is split.
+        let (parts, rest) = split_description("Plan:\n- ship\n- docs\n\nAsk Sam first.\n\n- later");
+        assert_eq!(parts, vec!["ship", "docs", "later"]);
+        assert_eq!(rest, "Plan:\n\nAsk Sam first.");
+        assert_eq!(split_description("One.\n\nTwo.").1, "");
+    }
+}
@@ -1,225 +1,174 @@
+use ratatui::layout::{Alignment, Margin};
+
+use super::board::Handler as BoardHandler;
+use super::detail::Handler as DetailHandler;
+use super::history::Handler as HistoryHandler;
+use super::palette::Handler as PaletteHandler;
+use super::review::Handler as ReviewHandler;
+use super::search::Handler as SearchHandler;
+use super::stats::Handler as StatsHandler;
+use super::timeline::Handler as TimelineHandler;
+use super::views::Handler as ViewsHandler;
 use super::{
-    App, Block, Borders, Color, Constraint, Direction, Focus, Frame, Layout, Line, Paragraph,
-    Position, Rect, Style,
+    App, Block, Borders, Color, Constraint, Direction, Frame, Layout, Line, Mode, Paragraph, Rect,
+    Span, Style,
 };
 use crate::app::utils::centered_rect;
 
-pub trait Render<'a> {
-    fn render_write_mode(&mut self, frame: &mut Frame, area: Rect);
-    fn render_edit_mode(&mut self, frame: &mut Frame, area: Rect);
-}
-
-impl<'a> Render<'a> for App<'a> {
-    fn render_write_mode(&mut self, frame: &mut Frame, area: Rect) {
-        let rect = centered_rect(40, 40, area);
+/// Below this size the popups can't fit, so only a notice is drawn.
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 13;
 
-        let rect_child = centered_rect(90, 90, rect);
-
-        let active_style = Style::new().fg(Color::Green);
-        let passive_style = Style::new().fg(Color::DarkGray);
+pub trait Render {
+    fn draw(&mut self, frame: &mut Frame);
+    fn render_list(&mut self, frame: &mut Frame, area: Rect);
+    fn render_too_small(&mut self, frame: &mut Frame, area: Rect, needed: (u16, u16));
+}
 
-        let active_button_style = Style::new().fg(Color::Green);
-        let passive_button_style = Style::new().fg(Color::DarkGray);
-        let button_style;
+impl Render for App {
+    fn draw(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        // The idea popup needs more room than the rest.
+        let needed = match (&self.form, &self.mode) {
+            (Some(form), Mode::Form) => form.min_size(),
+            _ => (MIN_WIDTH, MIN_HEIGHT),
+        };
+        if area.width < needed.0 || area.height < needed.1 {
+            self.render_too_small(frame, area, needed);
+            return;
+        }
 
-        let block = Block::new()
+        let mut title = String::from("  Ideas");
+        if let Some((filter, _)) = &self.filter {
+            title.push_str(&format!(" · {}", filter));
+        }
+        if let Some(sort) = &self.sort {
+            let order = if sort.descending { " desc" } else { "" };
+            title.push_str(&format!(" · sorted by {}{}", sort.field, order));
+        }
+        if self.selecting {
+            title.push_str(&format!(" · {} selected", self.selected.len()));
+        }
+        title.push_str("  ");
+        let title = Span::styled(title, Style::default().fg(Color::Green));
+        let mut block = Block::default()
+            .title(title)
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::DarkGray));
+        if let Some(status) = &self.status {
+            let text = Span::styled(
+                format!(" {} ", status.text),
+                Style::default().fg(Color::Yellow),
+            );
+            block = block.title_bottom(Line::from(text).right_aligned());
+        }
+        frame.render_widget(block, area);
 
-        let [title_area, description_area, footer_area] = Layout::default()
+        let [_, mut body_area] = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),
-                Constraint::Min(1),
-                Constraint::Length(2),
-            ])
-            .areas(rect_child);
-
-        // let title_text = &self.buffer[0];
-        // let description_text = &self.buffer[1];
-
-        let title_style;
-        let desc_style;
-        let border_style;
-        let active_border_style = Borders::NONE;
-        let passive_border_style = Borders::NONE;
-
-        match self.focus.as_ref().unwrap() {
-            Focus::Title => {
-                title_style = active_style;
-                desc_style = passive_style;
-                button_style = passive_button_style;
-                border_style = passive_border_style;
-
-                self.cursor_offset.0 = title_area.x + 1;
-                self.cursor_offset.1 = title_area.y + 1;
-
-                self.title_view.set_width(48);
-                frame.set_cursor_position(Position::new(
-                    self.cursor_offset.0 + self.title_view.get_last_word_cursor_position().0,
-                    self.cursor_offset.1,
-                ));
-            }
-            Focus::Description => {
-                title_style = passive_style;
-                desc_style = active_style;
-                button_style = passive_button_style;
-                border_style = passive_border_style;
-
-                self.cursor_offset.0 = description_area.x + 1;
-                self.cursor_offset.1 = description_area.y + 1;
-
-                frame.set_cursor_position(Position::new(
-                    self.cursor_offset.0 + self.description_view.get_last_word_cursor_position().0,
-                    self.description_view.get_last_word_cursor_position().1 + self.cursor_offset.1,
-                ));
-            }
-
-            Focus::Add => {
-                title_style = passive_style;
-                desc_style = passive_style;
-                button_style = active_button_style;
-                border_style = active_border_style;
-            }
-        }
-
-        let title_block = Block::default()
-            .borders(Borders::ALL)
-            .title("Title")
-            .style(title_style);
+            .constraints([Constraint::Max(2), Constraint::Min(1)])
+            .areas(area);
+
+        if !self.views.is_empty() {
+            let [sidebar_area, list_area] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(22), Constraint::Min(10)])
+                .areas(body_area);
+            self.render_sidebar(frame, sidebar_area);
+            body_area = list_area.inner(Margin::new(1, 0));
+        }
 
-        self.title_view.set_width(title_area.width);
-        self.title_view.set_buffer(&self.buffer[0]);
+        if self.board {
+            self.list_area = Rect::default();
+            self.list_rows.clear();
+            self.render_board(frame, body_area);
+        } else {
+            self.render_list(frame, body_area);
+        }
 
-        let desc_block = Block::default()
-            .borders(Borders::ALL)
-            .title("Description")
-            .style(desc_style);
+        if self.mode == Mode::Form
+            && let Some(form) = self.form.as_mut()
+        {
+            form.render(frame, area);
+        }
+
+        if self.mode == Mode::History {
+            self.render_history(frame, area);
+        }
+
+        if self.mode == Mode::Detail {
+            self.render_detail(frame, area);
+        }
 
-        self.description_view.set_width(title_area.width);
-        self.description_view.set_buffer(&self.buffer[1]);
+        if self.mode == Mode::Timeline {
+            self.render_timeline(frame, area);
+        }
 
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(40),
-                Constraint::Length(14), // button width
-                Constraint::Percentage(40),
-            ])
-            .split(footer_area);
+        if self.mode == Mode::Review {
+            self.render_review(frame, area);
+        }
 
-        let button_area = chunks[1];
-        let button_block = Block::default().borders(border_style).style(button_style);
+        if self.mode == Mode::Stats {
+            self.render_stats(frame, area);
+        }
 
-        let button_text = Paragraph::new("  + Add Idea").block(button_block);
+        if self.mode == Mode::Palette {
+            self.render_palette(frame, area);
+        }
 
-        frame.render_widget(block, rect);
+        if self.mode == Mode::Search {
+            self.render_search(frame, area);
+        }
 
-        self.title_view.render(frame, title_block, title_area);
-        self.description_view
-            .render(frame, desc_block, description_area);
-        frame.render_widget(button_text, button_area);
+        if let Some(modal) = self.modals.last() {
+            modal.render(frame, area);
+        }
     }
 
-    fn render_edit_mode(&mut self, frame: &mut Frame, area: Rect) {
-        let rect = centered_rect(40, 40, area);
+    fn render_list(&mut self, frame: &mut Frame, area: Rect) {
+        let (ideas, rows) = self.get_ideas_widget();
+
+        // Keep every line of the active idea inside the list area.
+        let first = rows
+            .iter()
+            .position(|i| *i == self.active_index)
+            .unwrap_or(0) as u16;
+        let last = rows
+            .iter()
+            .rposition(|i| *i == self.active_index)
+            .unwrap_or(0) as u16;
+        let height = area.height.max(1);
+        let mut offset = self.list_offset;
+        if last >= offset + height {
+            offset = last + 1 - height;
+        }
+        if first < offset {
+            offset = first;
+        }
 
-        let rect_child = centered_rect(90, 90, rect);
+        let para = Paragraph::new(ideas)
+            .style(Style::default())
+            .scroll((offset, 0));
 
-        let active_style = Style::new().fg(Color::Green);
-        let passive_style = Style::new().fg(Color::DarkGray);
+        frame.render_widget(para, area);
 
-        let active_button_style = Style::new().fg(Color::Green);
-        let passive_button_style = Style::new().fg(Color::DarkGray);
-        let button_style;
+        self.list_offset = offset;
+        self.list_rows = rows;
+        self.list_area = area;
+    }
 
-        let block = Block::new()
-            .borders(Borders::ALL)
+    fn render_too_small(&mut self, frame: &mut Frame, area: Rect, needed: (u16, u16)) {
+        self.list_area = Rect::default();
+
+        let lines = vec![
+            Line::styled("Terminal too small", Style::default().fg(Color::Red)),
+            Line::from(format!("{}x{}", area.width, area.height)),
+            Line::from(format!("needs {}x{}", needed.0, needed.1)),
+        ];
+        let rect = centered_rect(100, 60, area);
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
             .style(Style::default().fg(Color::DarkGray));
-
-        let [title_area, description_area, footer_area] = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),
-                Constraint::Min(1),
-                Constraint::Length(2),
-            ])
-            .areas(rect_child);
-
-        let title_text = &self.buffer[0];
-        let description_text = &self.buffer[1];
-        let title_style;
-        let desc_style;
-        let border_style;
-        let active_border_style = Borders::NONE;
-        let passive_border_style = Borders::NONE;
-
-        match self.focus.as_ref().unwrap() {
-            Focus::Title => {
-                title_style = active_style;
-                desc_style = passive_style;
-                button_style = passive_button_style;
-                border_style = passive_border_style;
-
-                // self.cursor_position.0 = title_area.x + self.buffer[0].len() as u16 + 1;
-                // self.cursor_position.1 = title_area.y + 1;
-
-                frame.set_cursor_position(Position::new(
-                    self.cursor_position.0,
-                    self.cursor_position.1,
-                ));
-            }
-            Focus::Description => {
-                title_style = passive_style;
-                desc_style = active_style;
-                button_style = passive_button_style;
-                border_style = passive_border_style;
-
-                // self.cursor_position.0 = description_area.x + self.buffer[1].len() as u16 + 1;
-                // self.cursor_position.1 = description_area.y + 1;
-
-                frame.set_cursor_position(Position::new(
-                    self.cursor_position.0,
-                    self.cursor_position.1,
-                ));
-            }
-            Focus::Add => {
-                title_style = passive_style;
-                desc_style = passive_style;
-                button_style = active_button_style;
-                border_style = active_border_style;
-            }
-        }
-
-        let title_input = Paragraph::new(Line::from(title_text.clone()))
-            .block(Block::default().borders(Borders::ALL).title("Title"))
-            .style(title_style);
-
-        let description_input = Paragraph::new(Line::from(description_text.clone())).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Description")
-                .style(desc_style),
-        );
-
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(40),
-                Constraint::Length(14), // button width
-                Constraint::Percentage(40),
-            ])
-            .split(footer_area);
-
-        let button_area = chunks[1];
-        let button_block = Block::default().borders(border_style).style(button_style);
-
-        let button_text = Paragraph::new("  📝 Edit Idea").block(button_block);
-
-        frame.render_widget(block, rect);
-        frame.render_widget(title_input, title_area);
-
-        frame.render_widget(description_input, description_area);
-        frame.render_widget(button_text, button_area);
+        frame.render_widget(para, rect);
     }
 }
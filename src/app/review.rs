@@ -0,0 +1,188 @@
+use chrono::{Days, NaiveDate};
+use ratatui::widgets::{Clear, Wrap};
+
+use super::idea::{Idea, Status, now};
+use super::modal::{Action, Handler as ModalHandler, Modal};
+use super::outline::Handler as OutlineHandler;
+use super::state::Handler as StateHandler;
+use super::utils::{format_timestamp, popup_rect, today};
+use super::{
+    App, Block, Borders, Color, Frame, KeyCode, KeyEvent, Line, Mode, Paragraph, Rect, Span, Style,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Whether `idea` is due for a review: it's still open, nobody has changed or
+/// reviewed it for `stale_days` and it isn't snoozed until a later date.
+pub fn is_stale(idea: &Idea, stale_days: u64, now: u64, today: NaiveDate) -> bool {
+    let last_seen = idea.updated_at.max(idea.last_reviewed_at.unwrap_or(0));
+    idea.status != Status::Done
+        && !idea.archived
+        && idea.review.is_none_or(|date| date <= today)
+        && now.saturating_sub(last_seen) >= stale_days * SECONDS_PER_DAY
+}
+
+/// Walks through the stale ideas one at a time with quick actions for each.
+pub trait Handler {
+    fn open_review(&mut self);
+    fn handle_review_key(&mut self, key_event: KeyEvent);
+    fn render_review(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl App {
+    /// The index of the idea under review, dropping ids that were deleted in
+    /// the meantime.
+    fn review_current(&mut self) -> Option<usize> {
+        while let Some(id) = self.review_queue.first() {
+            match self.ideas.iter().position(|i| i.id == *id) {
+                Some(index) => return Some(index),
+                None => {
+                    self.review_queue.remove(0);
+                }
+            }
+        }
+        None
+    }
+
+    /// Takes a deleted idea out of the queue, leaving the review when it was
+    /// the last one.
+    pub fn drop_from_review(&mut self, id: u64) {
+        self.review_queue.retain(|queued| *queued != id);
+        if self.mode == Mode::Review && self.review_current().is_none() {
+            self.mode = Mode::Read;
+            self.set_status("Review done");
+        }
+    }
+
+    /// Records the review of the current idea and moves on to the next.
+    fn review_next(&mut self, index: usize) {
+        self.ideas[index].last_reviewed_at = Some(now());
+        self.schedule_save();
+        self.review_queue.remove(0);
+        if self.review_current().is_none() {
+            self.mode = Mode::Read;
+            self.set_status("Review done");
+        }
+    }
+}
+
+impl Handler for App {
+    fn open_review(&mut self) {
+        let (now, today) = (now(), today());
+        let stale_days = self.config.review.stale_days;
+        self.review_queue = self
+            .outline_rows()
+            .into_iter()
+            .map(|(index, _)| &self.ideas[index])
+            .filter(|idea| is_stale(idea, stale_days, now, today))
+            .map(|idea| idea.id)
+            .collect();
+        if self.review_queue.is_empty() {
+            self.set_status(&format!(
+                "Nothing to review, every idea was seen in the last {} days",
+                stale_days
+            ));
+        } else {
+            self.mode = Mode::Review;
+        }
+    }
+
+    fn handle_review_key(&mut self, key_event: KeyEvent) {
+        let Some(index) = self.review_current() else {
+            self.mode = Mode::Read;
+            return;
+        };
+        self.active_index = index;
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = Mode::Read;
+                self.reveal(index);
+                let message = format!("Review paused, {} left", self.review_queue.len());
+                self.set_status(&message);
+            }
+            KeyCode::Char('k') | KeyCode::Enter => self.review_next(index),
+            KeyCode::Char('z') => {
+                self.ideas[index].review = today().checked_add_days(Days::new(7));
+                self.review_next(index);
+            }
+            KeyCode::Char('s') => self.cycle_status(),
+            KeyCode::Char('a') => {
+                let idea = &mut self.ideas[index];
+                idea.archived = true;
+                idea.touch();
+                self.review_next(index);
+            }
+            KeyCode::Char('d') => {
+                let message = format!("Delete \"{}\"?", self.ideas[index].title);
+                let action = Action::DeleteIdea(self.ideas[index].id);
+                self.open_modal(Modal::confirm("Delete", &message, action));
+            }
+            _ => {}
+        }
+    }
+
+    fn render_review(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(index) = self.review_current() else {
+            return;
+        };
+        let idea = &self.ideas[index];
+        let rect = popup_rect(60, 60, (40, 13), (90, 30), area);
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+
+        let last_seen = match idea.last_reviewed_at {
+            Some(at) if at > idea.updated_at => format!("reviewed {}", format_timestamp(at)),
+            _ => format!("updated {}", format_timestamp(idea.updated_at)),
+        };
+        let mut lines = vec![
+            Line::styled(idea.title.clone(), active_style),
+            Line::from(vec![
+                Span::styled(idea.status.label(), text_style),
+                Span::styled(format!("  ·  {}", last_seen), passive_style),
+            ]),
+            Line::from(""),
+        ];
+        for paragraph in idea.description.lines() {
+            lines.push(Line::styled(paragraph.to_string(), text_style));
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Review · {} left ", self.review_queue.len()))
+            .title_bottom(" k keep  z snooze a week  s status  a archive  d delete  esc pause ")
+            .style(passive_style);
+        let para = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+        frame.render_widget(Clear, rect);
+        frame.render_widget(para, rect);
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_is_stale() {
+        use crate::app::idea::{Idea, Status};
+        use crate::app::review::is_stale;
+        use chrono::NaiveDate;
+
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let day = 24 * 60 * 60;
+        let mut idea = Idea::new("old", "");
+        let now = idea.updated_at + 31 * day;
+        assert!(is_stale(&idea, 30, now, today));
+        assert!(!is_stale(&idea, 40, now, today));
+
+        idea.last_reviewed_at = Some(now - day);
+        assert!(!is_stale(&idea, 30, now, today));
+
+        idea.last_reviewed_at = None;
+        idea.review = NaiveDate::from_ymd_opt(2025, 1, 20);
+        assert!(!is_stale(&idea, 30, now, today));
+
+        idea.review = None;
+        idea.status = Status::Done;
+        assert!(!is_stale(&idea, 30, now, today));
+    }
+}
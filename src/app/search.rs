@@ -0,0 +1,640 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ratatui::widgets::Clear;
+use serde_json::Value;
+
+use super::idea::{Idea, fnv1a};
+use super::state::Handler as StateHandler;
+use super::utils::{popup_rect, truncate};
+use super::{
+    App, Block, Borders, Color, Frame, KeyCode, KeyEvent, KeyModifiers, Line, Mode, Paragraph,
+    Rect, Span, Style,
+};
+
+/// Bumped whenever tokenizing or the file layout changes, so indexes written
+/// by other versions are rebuilt instead of read.
+const SCHEMA: u32 = 3;
+
+/// BM25 term frequency saturation and length normalization.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Words too common to say anything about an idea.
+const STOP_WORDS: [&str; 24] = [
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "into", "is", "it", "of",
+    "on", "or", "that", "the", "this", "to", "was", "with", "we",
+];
+
+/// Porter's step 2 and 3 endings with what replaces them, taken off when
+/// what's left has a vowel-consonant sequence.
+const STEP_2: [(&str, &str); 20] = [
+    ("ational", "ate"),
+    ("tional", "tion"),
+    ("enci", "ence"),
+    ("anci", "ance"),
+    ("izer", "ize"),
+    ("abli", "able"),
+    ("alli", "al"),
+    ("entli", "ent"),
+    ("eli", "e"),
+    ("ousli", "ous"),
+    ("ization", "ize"),
+    ("ation", "ate"),
+    ("ator", "ate"),
+    ("alism", "al"),
+    ("iveness", "ive"),
+    ("fulness", "ful"),
+    ("ousness", "ous"),
+    ("aliti", "al"),
+    ("iviti", "ive"),
+    ("biliti", "ble"),
+];
+const STEP_3: [(&str, &str); 7] = [
+    ("icate", "ic"),
+    ("ative", ""),
+    ("alize", "al"),
+    ("iciti", "ic"),
+    ("ical", "ic"),
+    ("ful", ""),
+    ("ness", ""),
+];
+
+/// Where the index of the store at `store` is kept.
+pub fn index_path(store: &Path) -> PathBuf {
+    store.with_extension("index.json")
+}
+
+fn is_consonant(word: &[char], i: usize) -> bool {
+    match word[i] {
+        'a' | 'e' | 'i' | 'o' | 'u' => false,
+        'y' => i == 0 || !is_consonant(word, i - 1),
+        _ => true,
+    }
+}
+
+/// How many vowel-consonant sequences `word` has, Porter's m.
+fn measure(word: &[char]) -> usize {
+    let mut count = 0;
+    let mut vowel = false;
+    for i in 0..word.len() {
+        if !is_consonant(word, i) {
+            vowel = true;
+        } else if vowel {
+            count += 1;
+            vowel = false;
+        }
+    }
+    count
+}
+
+fn has_vowel(word: &[char]) -> bool {
+    (0..word.len()).any(|i| !is_consonant(word, i))
+}
+
+/// Whether `word` ends consonant-vowel-consonant, the last not w, x or y,
+/// as in "hop", where a dropped "e" is put back.
+fn ends_cvc(word: &[char]) -> bool {
+    let n = word.len();
+    n >= 3
+        && is_consonant(word, n - 3)
+        && !is_consonant(word, n - 2)
+        && is_consonant(word, n - 1)
+        && !matches!(word[n - 1], 'w' | 'x' | 'y')
+}
+
+/// Whether `word` ends with the ascii `suffix`.
+fn ends_with(word: &[char], suffix: &str) -> bool {
+    word.len() >= suffix.len()
+        && word[word.len() - suffix.len()..]
+            .iter()
+            .copied()
+            .eq(suffix.chars())
+}
+
+/// Replaces the first of `rules` that `word` ends with, when what's left
+/// has a measure above zero. Returns whether an ending matched at all.
+fn replace_ending(word: &mut Vec<char>, rules: &[(&str, &str)]) -> bool {
+    for (suffix, replacement) in rules {
+        if word.len() > suffix.len() && ends_with(word, suffix) {
+            let stem = word.len() - suffix.len();
+            if measure(&word[..stem]) > 0 {
+                word.truncate(stem);
+                word.extend(replacement.chars());
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// A word without its common English endings, by the first three steps of
+/// the Porter stemmer, so that e.g. "running" and "runs" find "run".
+fn stem(word: &str) -> String {
+    let mut word: Vec<char> = word.chars().collect();
+    if word.len() <= 2 {
+        return word.into_iter().collect();
+    }
+    // Step 1a: plurals.
+    if ends_with(&word, "sses") || ends_with(&word, "ies") {
+        word.truncate(word.len() - 2);
+    } else if !ends_with(&word, "ss") && ends_with(&word, "s") {
+        word.pop();
+    }
+
+    // Step 1b: -ed and -ing, fixing up what's left.
+    if ends_with(&word, "eed") {
+        if measure(&word[..word.len() - 3]) > 0 {
+            word.pop();
+        }
+    } else {
+        let length = if ends_with(&word, "ed") {
+            2
+        } else if ends_with(&word, "ing") {
+            3
+        } else {
+            0
+        };
+        if length > 0 && has_vowel(&word[..word.len() - length]) {
+            word.truncate(word.len() - length);
+            let n = word.len();
+            if ends_with(&word, "at") || ends_with(&word, "bl") || ends_with(&word, "iz") {
+                word.push('e');
+            } else if n >= 2
+                && word[n - 1] == word[n - 2]
+                && is_consonant(&word, n - 1)
+                && !matches!(word[n - 1], 'l' | 's' | 'z')
+            {
+                word.pop();
+            } else if measure(&word) == 1 && ends_cvc(&word) {
+                word.push('e');
+            }
+        }
+    }
+
+    // Step 1c: a final y after a vowel-bearing stem.
+    if ends_with(&word, "y") && has_vowel(&word[..word.len() - 1]) {
+        let n = word.len();
+        word[n - 1] = 'i';
+    }
+
+    replace_ending(&mut word, &STEP_2);
+    replace_ending(&mut word, &STEP_3);
+    word.into_iter().collect()
+}
+
+/// The terms of `text`: lowercase stemmed words without the stop words.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .map(|w| stem(&w))
+        .collect()
+}
+
+/// The text of an idea that's searched. The title counts twice so matches
+/// in it rank higher.
+fn indexed_text(idea: &Idea) -> String {
+    let mut text = format!("{0}\n{0}\n{1}", idea.title, idea.description);
+    for item in &idea.checklist {
+        text.push('\n');
+        text.push_str(&item.text);
+    }
+    for tag in &idea.tags {
+        text.push('\n');
+        text.push_str(tag);
+    }
+    for value in idea.extra.values() {
+        if let Value::String(string) = value {
+            text.push('\n');
+            text.push_str(string);
+        }
+    }
+    text
+}
+
+fn fingerprint(text: &str) -> u64 {
+    fnv1a(text.bytes())
+}
+
+/// Ids of the ideas added, edited or deleted between `before` and `after`.
+pub fn changed_ids(before: &[Idea], after: &[Idea]) -> HashSet<u64> {
+    let before_map: HashMap<u64, &Idea> = before.iter().map(|i| (i.id, i)).collect();
+    let mut changed: HashSet<u64> = after
+        .iter()
+        .filter(|idea| before_map.get(&idea.id) != Some(idea))
+        .map(|idea| idea.id)
+        .collect();
+    let after_ids: HashSet<u64> = after.iter().map(|i| i.id).collect();
+    changed.extend(
+        before
+            .iter()
+            .map(|i| i.id)
+            .filter(|id| !after_ids.contains(id)),
+    );
+    changed
+}
+
+/// What the index holds about one idea.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+struct Document {
+    /// A hash of the indexed text, to tell when the idea needs indexing
+    /// again after loading.
+    fingerprint: u64,
+    length: u32,
+    terms: Vec<String>,
+}
+
+/// An inverted index over the ideas' text, ranking matches with BM25. A saved
+/// index is brought up to date by `sync`, which only reindexes the ideas whose
+/// text changed; after that `update` reindexes just the ideas an edit touched,
+/// so it stays cheap on large stores.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct SearchIndex {
+    schema: u32,
+    documents: HashMap<u64, Document>,
+    /// For each term, the ideas it occurs in and how often.
+    postings: BTreeMap<String, HashMap<u64, u32>>,
+    total_length: u64,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            schema: SCHEMA,
+            documents: HashMap::new(),
+            postings: BTreeMap::new(),
+            total_length: 0,
+        }
+    }
+}
+
+impl SearchIndex {
+    /// Reads a saved index. One that's missing, unreadable or from another
+    /// schema gives an empty index, which `sync` fills again.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<SearchIndex>(&bytes).ok())
+            .filter(|index| index.schema == SCHEMA)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut tmp_path = path.to_path_buf().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+        std::fs::rename(&tmp_path, path)
+    }
+
+    /// How many ideas are indexed.
+    pub fn indexed(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Indexes added and edited ideas and drops deleted ones, returning
+    /// whether anything changed.
+    pub fn sync(&mut self, ideas: &[Idea]) -> bool {
+        let mut changed = false;
+        let mut seen = HashSet::with_capacity(ideas.len());
+        for idea in ideas {
+            let text = indexed_text(idea);
+            let print = fingerprint(&text);
+            seen.insert(idea.id);
+            if self
+                .documents
+                .get(&idea.id)
+                .is_some_and(|d| d.fingerprint == print)
+            {
+                continue;
+            }
+            self.remove(idea.id);
+            self.insert(idea.id, &text, print);
+            changed = true;
+        }
+        let gone: Vec<u64> = self
+            .documents
+            .keys()
+            .filter(|id| !seen.contains(id))
+            .copied()
+            .collect();
+        for id in gone {
+            self.remove(id);
+            changed = true;
+        }
+        changed
+    }
+
+    /// Reindexes the ideas with `ids`, dropping the ones no longer in
+    /// `ideas`. Returns whether there were any.
+    pub fn update(&mut self, ideas: &[Idea], ids: &HashSet<u64>) -> bool {
+        for id in ids {
+            self.remove(*id);
+        }
+        for idea in ideas.iter().filter(|i| ids.contains(&i.id)) {
+            let text = indexed_text(idea);
+            self.insert(idea.id, &text, fingerprint(&text));
+        }
+        !ids.is_empty()
+    }
+
+    fn insert(&mut self, id: u64, text: &str, fingerprint: u64) {
+        let tokens = tokenize(text);
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *counts.entry(token.clone()).or_default() += 1;
+        }
+        for (term, count) in &counts {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(id, *count);
+        }
+        self.total_length += tokens.len() as u64;
+        self.documents.insert(
+            id,
+            Document {
+                fingerprint,
+                length: tokens.len() as u32,
+                terms: counts.into_keys().collect(),
+            },
+        );
+    }
+
+    fn remove(&mut self, id: u64) {
+        let Some(document) = self.documents.remove(&id) else {
+            return;
+        };
+        for term in &document.terms {
+            if let Some(postings) = self.postings.get_mut(term) {
+                postings.remove(&id);
+                if postings.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total_length -= document.length as u64;
+    }
+
+    /// Ids of the ideas matching `query`, best first, with their scores. A
+    /// word that isn't in the index matches the words it starts, so results
+    /// show up while it's being typed.
+    pub fn search(&self, query: &str) -> Vec<(u64, f64)> {
+        let count = self.documents.len() as f64;
+        if count == 0.0 {
+            return Vec::new();
+        }
+        let average = (self.total_length as f64 / count).max(1.0);
+        let mut scores: HashMap<u64, f64> = HashMap::new();
+        for term in tokenize(query) {
+            let postings: Vec<&HashMap<u64, u32>> = match self.postings.get(&term) {
+                Some(postings) => vec![postings],
+                None => self
+                    .postings
+                    .range(term.clone()..)
+                    .take_while(|(key, _)| key.starts_with(&term))
+                    .map(|(_, postings)| postings)
+                    .collect(),
+            };
+            for postings in postings {
+                let frequency = postings.len() as f64;
+                let idf = (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln();
+                for (id, tf) in postings {
+                    let length = self.documents[id].length as f64;
+                    let tf = *tf as f64;
+                    let score =
+                        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average));
+                    *scores.entry(*id).or_default() += score;
+                }
+            }
+        }
+        let mut results: Vec<(u64, f64)> = scores.into_iter().collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        results
+    }
+}
+
+/// Searching the text of all ideas with the index, ranked by relevance.
+pub trait Handler {
+    fn open_search(&mut self);
+    /// Brings a loaded index up to date with the ideas.
+    fn sync_search(&mut self);
+    /// Reindexes the ideas with `ids` after an edit touched them.
+    fn update_search(&mut self, ids: &HashSet<u64>);
+    /// Writes the index next to the store, unless the store is encrypted:
+    /// the index would give its words away.
+    fn save_search(&mut self);
+    fn handle_search_key(&mut self, key_event: KeyEvent);
+    fn render_search(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl App {
+    /// Indices of the ideas matching the search input, best first.
+    fn search_results(&self) -> Vec<usize> {
+        let positions: HashMap<u64, usize> = self
+            .ideas
+            .iter()
+            .enumerate()
+            .map(|(i, idea)| (idea.id, i))
+            .collect();
+        self.search
+            .search(&self.search_input)
+            .into_iter()
+            .filter_map(|(id, _)| positions.get(&id).copied())
+            .collect()
+    }
+}
+
+impl Handler for App {
+    fn open_search(&mut self) {
+        let unsaved = changed_ids(self.store.base(), &self.ideas);
+        self.update_search(&unsaved);
+        self.search_input.clear();
+        self.search_index = 0;
+        self.mode = Mode::Search;
+    }
+
+    fn sync_search(&mut self) {
+        if self.search.sync(&self.ideas) {
+            self.search_dirty = true;
+        }
+    }
+
+    fn update_search(&mut self, ids: &HashSet<u64>) {
+        if self.search.update(&self.ideas, ids) {
+            self.search_dirty = true;
+        }
+    }
+
+    fn save_search(&mut self) {
+        if !self.search_dirty || self.store.is_encrypted() {
+            return;
+        }
+        match self.search.save(&index_path(self.store.path())) {
+            Ok(()) => self.search_dirty = false,
+            Err(err) => self.set_status(&format!("Couldn't save the search index: {}", err)),
+        }
+    }
+
+    fn handle_search_key(&mut self, key_event: KeyEvent) {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let count = self.search_results().len();
+        match key_event.code {
+            KeyCode::Esc => self.mode = Mode::Read,
+            KeyCode::Up => self.search_index = self.search_index.saturating_sub(1),
+            KeyCode::Char('p') if control => {
+                self.search_index = self.search_index.saturating_sub(1);
+            }
+            KeyCode::Down if self.search_index + 1 < count => self.search_index += 1,
+            KeyCode::Char('n') if control && self.search_index + 1 < count => {
+                self.search_index += 1;
+            }
+            KeyCode::Char('u') if control => {
+                self.search_input.clear();
+                self.search_index = 0;
+            }
+            KeyCode::Backspace => {
+                self.search_input.pop();
+                self.search_index = 0;
+            }
+            KeyCode::Char(c) if !control => {
+                self.search_input.push(c);
+                self.search_index = 0;
+            }
+            KeyCode::Enter => {
+                if let Some(index) = self.search_results().get(self.search_index) {
+                    self.mode = Mode::Read;
+                    self.go_to_idea(*index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn render_search(&mut self, frame: &mut Frame, area: Rect) {
+        let rect = popup_rect(60, 60, (40, 13), (90, 30), area);
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+
+        let results = self.search_results();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Search · {} ideas ", self.search.indexed()))
+            .title_bottom(" ↑/↓ move  enter go to idea  esc close ")
+            .style(passive_style);
+        let inner = block.inner(rect);
+        let width = inner.width.saturating_sub(2) as usize;
+        frame.render_widget(Clear, rect);
+        frame.render_widget(block, rect);
+
+        let prompt = Line::from(vec![
+            Span::styled("/ ", active_style),
+            Span::styled(self.search_input.clone(), text_style),
+        ]);
+        frame.render_widget(Paragraph::new(prompt), Rect { height: 1, ..inner });
+        frame.set_cursor_position((
+            inner.x + 2 + self.search_input.chars().count() as u16,
+            inner.y,
+        ));
+
+        let list = Rect {
+            y: inner.y + 2,
+            height: inner.height.saturating_sub(2),
+            ..inner
+        };
+        let mut lines: Vec<Line> = results
+            .iter()
+            .enumerate()
+            .map(|(i, index)| {
+                let (prefix, style) = if i == self.search_index {
+                    ("> ", active_style)
+                } else {
+                    ("  ", text_style)
+                };
+                let idea = &self.ideas[*index];
+                let title = truncate(&idea.title, width / 2);
+                let rest = width.saturating_sub(title.chars().count() + 2);
+                let description = truncate(&idea.description, rest);
+                Line::from(vec![
+                    Span::styled(prefix, style),
+                    Span::styled(title, style),
+                    Span::styled(format!("  {}", description), passive_style),
+                ])
+            })
+            .collect();
+        if lines.is_empty() && !self.search_input.trim().is_empty() {
+            lines.push(Line::styled("  No matches", passive_style));
+        }
+        let height = list.height.max(1);
+        let offset = (self.search_index as u16).saturating_sub(height - 1);
+        frame.render_widget(Paragraph::new(lines).scroll((offset, 0)), list);
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_search_index() {
+        use crate::app::idea::Idea;
+        use crate::app::search::{SCHEMA, SearchIndex, changed_ids, fingerprint, tokenize};
+
+        assert_eq!(
+            tokenize("The Sockets, running tests!"),
+            vec!["socket", "run", "test"]
+        );
+        assert_eq!(
+            tokenize("run runs hopping hoped"),
+            vec!["run", "run", "hop", "hope"]
+        );
+
+        let mut socket = Idea::new("Socket server", "An async server for sockets");
+        socket.id = 1;
+        let mut garden = Idea::new("Garden", "Plant tomatoes near the server room");
+        garden.id = 2;
+        let mut ideas = vec![socket, garden];
+
+        let mut index = SearchIndex::default();
+        assert!(index.sync(&ideas));
+        assert!(!index.sync(&ideas));
+        let ids = |index: &SearchIndex, query: &str| -> Vec<u64> {
+            index.search(query).into_iter().map(|(id, _)| id).collect()
+        };
+        assert_eq!(ids(&index, "server"), vec![1, 2]);
+        assert_eq!(ids(&index, "tomato"), vec![2]);
+        assert_eq!(ids(&index, "gard"), vec![2]);
+
+        ideas[1].description = String::from("Plant peppers");
+        ideas.remove(0);
+        assert!(index.sync(&ideas));
+        assert_eq!(ids(&index, "server"), Vec::<u64>::new());
+        assert_eq!(ids(&index, "pepper"), vec![2]);
+
+        let before = ideas.clone();
+        ideas[0].title = String::from("Orchard");
+        let mut shed = Idea::new("Shed", "Somewhere for the tools");
+        shed.id = 3;
+        ideas.push(shed);
+        let touched = changed_ids(&before, &ideas);
+        assert_eq!(touched, [2, 3].into());
+        assert!(index.update(&ideas, &touched));
+        assert_eq!(ids(&index, "orchard tool"), vec![2, 3]);
+        assert_eq!(ids(&index, "garden"), Vec::<u64>::new());
+        assert!(!index.sync(&ideas));
+        assert_eq!(changed_ids(&ideas, &ideas[1..]), [2].into());
+        // Saved fingerprints have to mean the same in every build.
+        assert_eq!(fingerprint("server"), 0x7294d77db181ded2);
+
+        let dir = std::env::temp_dir().join(format!("ideas-search-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ideas.index.json");
+        index.save(&path).unwrap();
+        assert_eq!(SearchIndex::load(&path), index);
+        let old = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace(&format!("\"schema\":{}", SCHEMA), "\"schema\":0");
+        std::fs::write(&path, old).unwrap();
+        assert_eq!(SearchIndex::load(&path), SearchIndex::default());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
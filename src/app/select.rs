@@ -0,0 +1,251 @@
+use std::path::Path;
+
+use super::App;
+use super::idea::{Idea, Status, tags_from_text};
+use super::keymap::Command;
+use super::modal::{Action, Handler as ModalHandler, Modal};
+use super::outline::Handler as OutlineHandler;
+use super::state::Handler as StateHandler;
+
+/// Picking several ideas and acting on all of them at once. While selecting,
+/// Space marks ideas instead of collapsing them and the action keys apply to
+/// the marked ideas.
+pub trait Handler {
+    fn start_selecting(&mut self);
+    fn stop_selecting(&mut self);
+    /// Whether any idea is selected, telling the user how to select one
+    /// when not.
+    fn has_selection(&mut self) -> bool;
+    /// Asks for confirmation or input before running the bulk `command`.
+    fn confirm_selected(&mut self, command: Command);
+    fn toggle_selected(&mut self);
+    fn select_range(&mut self);
+    fn select_all(&mut self);
+    fn delete_selected(&mut self);
+    fn archive_selected(&mut self);
+    fn tag_selected(&mut self, text: &str, add: bool);
+    fn set_selected_status(&mut self, status: Status);
+    fn export_selected(&mut self, path: &str);
+}
+
+impl App {
+    /// Indices of the selected ideas in list order.
+    fn selected_indices(&self) -> Vec<usize> {
+        (0..self.ideas.len())
+            .filter(|i| self.selected.contains(&self.ideas[*i].id))
+            .collect()
+    }
+
+    fn announce_selection(&mut self) {
+        let message = format!("{} selected", self.selected.len());
+        self.set_status(&message);
+    }
+}
+
+impl Handler for App {
+    fn start_selecting(&mut self) {
+        self.selecting = true;
+        self.select_anchor = None;
+    }
+
+    fn stop_selecting(&mut self) {
+        self.selecting = false;
+        self.selected.clear();
+        self.select_anchor = None;
+    }
+
+    fn has_selection(&mut self) -> bool {
+        if self.selected.is_empty() {
+            self.set_status("Nothing selected, space marks an idea while selecting");
+            return false;
+        }
+        true
+    }
+
+    fn confirm_selected(&mut self, command: Command) {
+        if !self.has_selection() {
+            return;
+        }
+        let count = self.selected.len();
+        let modal = match command {
+            Command::DeleteSelected => Modal::confirm(
+                "Delete",
+                &format!("Delete {} ideas?", count),
+                Action::DeleteSelected,
+            ),
+            Command::MergeSelected => Modal::confirm(
+                "Merge",
+                &format!(
+                    "Merge {} ideas into the first of them? Descriptions, checklists and tags are combined.",
+                    count
+                ),
+                Action::MergeSelected,
+            ),
+            Command::TagSelected => Modal::prompt(
+                "Tag",
+                &format!("Tags to add to {} ideas", count),
+                "",
+                Action::TagSelected,
+            ),
+            Command::UntagSelected => Modal::prompt(
+                "Untag",
+                &format!("Tags to remove from {} ideas", count),
+                "",
+                Action::UntagSelected,
+            ),
+            Command::StatusSelected => Modal::select(
+                "Status",
+                &format!("Status of {} ideas", count),
+                Status::ALL.iter().map(|s| s.label().to_string()).collect(),
+                Action::SetSelectedStatus,
+            ),
+            Command::ExportSelected => Modal::prompt(
+                "Export",
+                &format!("File to write {} ideas to", count),
+                "ideas-export.json",
+                Action::ExportSelected,
+            ),
+            _ => return,
+        };
+        self.open_modal(modal);
+    }
+
+    fn toggle_selected(&mut self) {
+        let Some(id) = self.ideas.get(self.active_index).map(|i| i.id) else {
+            return;
+        };
+        if !self.selected.remove(&id) {
+            self.selected.insert(id);
+        }
+        self.select_anchor = Some(id);
+        self.announce_selection();
+    }
+
+    /// Selects the rows between the last idea marked with Space and the
+    /// selected one.
+    fn select_range(&mut self) {
+        let rows: Vec<usize> = self.outline_rows().into_iter().map(|(i, _)| i).collect();
+        let anchor = self
+            .select_anchor
+            .and_then(|id| rows.iter().position(|i| self.ideas[*i].id == id));
+        let Some(current) = rows.iter().position(|i| *i == self.active_index) else {
+            return;
+        };
+        let anchor = anchor.unwrap_or(current);
+        for index in &rows[anchor.min(current)..=anchor.max(current)] {
+            self.selected.insert(self.ideas[*index].id);
+        }
+        self.select_anchor = Some(self.ideas[self.active_index].id);
+        self.announce_selection();
+    }
+
+    /// Selects every idea in the list, or clears the selection when they're
+    /// all selected already.
+    fn select_all(&mut self) {
+        let ids: Vec<u64> = self
+            .outline_rows()
+            .into_iter()
+            .map(|(i, _)| self.ideas[i].id)
+            .collect();
+        if ids.iter().all(|id| self.selected.contains(id)) {
+            self.selected.clear();
+        } else {
+            self.selected.extend(ids);
+        }
+        self.announce_selection();
+    }
+
+    fn delete_selected(&mut self) {
+        let count = self.selected.len();
+        for id in std::mem::take(&mut self.selected) {
+            if let Some(index) = self.ideas.iter().position(|i| i.id == id) {
+                self.remove_idea(index);
+            }
+        }
+        if self.active_index >= self.ideas.len() {
+            self.active_index = self.ideas.len().saturating_sub(1);
+        }
+        self.stop_selecting();
+        self.schedule_save();
+        self.set_status(&format!("{} ideas deleted", count));
+    }
+
+    /// Archives the selected ideas, or brings them back when they're all
+    /// archived already.
+    fn archive_selected(&mut self) {
+        let indices = self.selected_indices();
+        let archive = !indices.iter().all(|i| self.ideas[*i].archived);
+        for index in &indices {
+            let idea = &mut self.ideas[*index];
+            if idea.archived != archive {
+                idea.archived = archive;
+                idea.touch();
+            }
+        }
+        self.schedule_save();
+        let verb = if archive { "archived" } else { "unarchived" };
+        self.set_status(&format!("{} ideas {}", indices.len(), verb));
+    }
+
+    fn tag_selected(&mut self, text: &str, add: bool) {
+        let tags = tags_from_text(text);
+        if tags.is_empty() {
+            return;
+        }
+        let mut changed = 0;
+        for index in self.selected_indices() {
+            let idea = &mut self.ideas[index];
+            let before = idea.tags.len();
+            if add {
+                let missing: Vec<String> = tags
+                    .iter()
+                    .filter(|t| !idea.tags.contains(t))
+                    .cloned()
+                    .collect();
+                idea.tags.extend(missing);
+            } else {
+                idea.tags.retain(|t| !tags.contains(t));
+            }
+            if idea.tags.len() != before {
+                idea.touch();
+                changed += 1;
+            }
+        }
+        self.schedule_save();
+        self.set_status(&format!("{} ideas changed", changed));
+    }
+
+    fn set_selected_status(&mut self, status: Status) {
+        let indices = self.selected_indices();
+        for index in &indices {
+            let idea = &mut self.ideas[*index];
+            if idea.status != status {
+                idea.status = status;
+                idea.touch();
+            }
+        }
+        self.schedule_save();
+        self.set_status(&format!("{} ideas {}", indices.len(), status.label()));
+    }
+
+    /// Writes the selected ideas to `path` in the store's json format.
+    fn export_selected(&mut self, path: &str) {
+        let path = path.trim();
+        if path.is_empty() {
+            return;
+        }
+        let ideas: Vec<&Idea> = self
+            .selected_indices()
+            .into_iter()
+            .map(|i| &self.ideas[i])
+            .collect();
+        let count = ideas.len();
+        let result = serde_json::to_string_pretty(&ideas)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(Path::new(path), json));
+        match result {
+            Ok(()) => self.set_status(&format!("{} ideas written to {}", count, path)),
+            Err(err) => self.set_status(&format!("Couldn't export: {}", err)),
+        }
+    }
+}
@@ -1,52 +1,338 @@
-use super::{App, Idea};
+use super::crypto::Cipher;
+use super::dedupe::Handler as DedupeHandler;
+use super::form::{IdeaForm, Kind};
+use super::git::describe_changes;
+use super::links;
+use super::merge::display_value;
+use super::modal::{Action, Handler as ModalHandler, Modal};
+use super::search::{Handler as SearchHandler, changed_ids};
+use super::utils::truncate;
+use super::{App, Conflict, Duration, Idea, Instant, Mode, StatusMessage};
+use std::io::ErrorKind;
 
-pub trait Handler<'a> {
-    fn add_idea(&mut self, title: &str, description: &str);
+const STATUS_DURATION: Duration = Duration::from_secs(3);
+
+pub trait Handler {
+    fn add_idea(&mut self, idea: Idea);
+    fn add_new_idea(&mut self, idea: Idea);
     fn remove_idea(&mut self, index: usize);
+    fn cycle_status(&mut self);
     fn save(&mut self);
-    fn clear_buffer(&mut self);
-    fn quit(&mut self);
+    fn reload(&mut self);
+    fn apply_merge(&mut self, ideas: Vec<Idea>, conflicts: Vec<Conflict>);
+    fn open_conflict(&mut self);
+    fn resolve_conflict(&mut self, theirs: &[usize]);
+    fn ask_passphrase(&mut self, reason: &str);
+    fn unlock(&mut self, passphrase: &str);
+    fn schedule_save(&mut self);
+    fn set_status(&mut self, text: &str);
+    fn quit(&mut self) -> std::io::Result<()>;
     fn save_edit(&mut self);
-    fn load_buffer(&mut self) -> bool;
+    fn submit_form(&mut self);
+    fn open_form(&mut self, form: IdeaForm);
+    fn close_form(&mut self);
+    fn has_unsaved_form(&self) -> bool;
 }
 
-impl<'a> Handler<'a> for App<'a> {
-    fn add_idea(&mut self, title: &str, description: &str) {
-        self.ideas.push(Idea::new(title, description));
+impl Handler for App {
+    fn add_idea(&mut self, mut idea: Idea) {
+        idea.links = links::link_ids(&self.ideas, &idea);
+        self.ideas.push(idea);
+    }
+
+    /// Adds an idea written in the form and clears it for the next one.
+    fn add_new_idea(&mut self, idea: Idea) {
+        if let Some(form) = self.form.as_mut() {
+            form.clear();
+        }
+        self.add_idea(idea);
+        self.set_status("Idea added");
+        self.schedule_save();
     }
 
+    /// Removes an idea, moving its sub-ideas up to its parent.
     fn remove_idea(&mut self, index: usize) {
-        self.ideas.remove(index);
+        let idea = self.ideas.remove(index);
+        for child in self.ideas.iter_mut().filter(|i| i.parent == Some(idea.id)) {
+            child.parent = idea.parent;
+            child.touch();
+        }
+    }
+
+    fn cycle_status(&mut self) {
+        let Some(idea) = self.ideas.get_mut(self.active_index) else {
+            return;
+        };
+        idea.status = idea.status.next();
+        idea.touch();
+        let message = format!("Status: {}", idea.status.label());
+        self.set_status(&message);
+        self.schedule_save();
     }
 
     fn save(&mut self) {
-        let string = serde_json::to_string_pretty(&self.ideas).unwrap();
-        std::fs::write(&self.path, string).unwrap();
+        self.pending_save = None;
+        let before = self.git.as_ref().map(|_| self.store.base().to_vec());
+        let touched = changed_ids(self.store.base(), &self.ideas);
+        match self.store.save(&self.ideas) {
+            Ok((ideas, conflicts)) => {
+                if let (Some(git), Some(before)) = (&self.git, before) {
+                    let message = describe_changes(&before, &ideas);
+                    if let Err(err) = git.commit(&message) {
+                        self.set_status(&format!("Couldn't commit: {}", err));
+                    }
+                }
+                self.update_search(&touched);
+                if ideas != self.ideas || !conflicts.is_empty() {
+                    self.apply_merge(ideas, conflicts);
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+                self.ask_passphrase(&err.to_string())
+            }
+            Err(err) => self.set_status(&format!("Couldn't save: {}", err)),
+        }
+    }
+
+    /// Merges changes someone else wrote to the store into the list.
+    fn reload(&mut self) {
+        match self.store.reload(&self.ideas) {
+            Ok((ideas, conflicts)) => {
+                if ideas != self.store.base() {
+                    self.schedule_save();
+                }
+                self.set_status("Reloaded changes from disk");
+                self.apply_merge(ideas, conflicts);
+            }
+            Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+                self.ask_passphrase(&err.to_string())
+            }
+            Err(err) => self.set_status(&format!("Couldn't reload: {}", err)),
+        }
     }
 
-    fn clear_buffer(&mut self) {
-        self.buffer[0].clear();
-        self.buffer[1].clear();
+    /// Locks the list until the passphrase of the encrypted store is entered.
+    /// Nothing is read or written meanwhile.
+    fn ask_passphrase(&mut self, reason: &str) {
+        if self.locked {
+            return;
+        }
+        self.locked = true;
+        self.store.set_cipher(None);
+        let message = if reason == "wrong passphrase" {
+            "Wrong passphrase, try again:"
+        } else {
+            "The idea store is encrypted. Enter its passphrase:"
+        };
+        self.open_modal(Modal::secret("Unlock", message, Action::Unlock));
+    }
+
+    fn unlock(&mut self, passphrase: &str) {
+        self.locked = false;
+        self.store.set_cipher(Some(Cipher::new(passphrase)));
+        self.reload();
+        if !self.locked {
+            self.set_status("Unlocked");
+        }
+    }
+
+    /// Takes a merged list from the store, keeping the selection on the same
+    /// idea and asking the user about conflicting edits.
+    fn apply_merge(&mut self, ideas: Vec<Idea>, conflicts: Vec<Conflict>) {
+        let active_id = self.ideas.get(self.active_index).map(|i| i.id);
+        let touched = changed_ids(&self.ideas, &ideas);
+        self.ideas = ideas;
+        self.update_search(&touched);
+        self.active_index = active_id
+            .and_then(|id| self.ideas.iter().position(|i| i.id == id))
+            .unwrap_or(self.active_index)
+            .min(self.ideas.len().saturating_sub(1));
+        self.dirty = true;
+
+        let changed_while_editing = self
+            .form
+            .as_ref()
+            .and_then(|f| f.original())
+            .and_then(|original| {
+                self.ideas
+                    .iter()
+                    .find(|i| i.id == original.id && *i != original)
+            })
+            .cloned();
+        if let Some(idea) = changed_while_editing {
+            let message = format!(
+                "\"{}\" changed on disk while you were editing it. Saving will overwrite that change.",
+                idea.title
+            );
+            if let Some(form) = self.form.as_mut() {
+                form.rebase(idea);
+            }
+            self.open_modal(Modal::message("Changed on disk", &message));
+        }
+
+        if !conflicts.is_empty() {
+            self.conflicts = conflicts;
+            self.open_conflict();
+        }
     }
 
-    fn quit(&mut self) {
+    /// Asks how to settle the first unresolved conflict: which fields to take
+    /// from disk, or whether to keep an idea one side deleted.
+    fn open_conflict(&mut self) {
+        let Some(conflict) = self.conflicts.first() else {
+            return;
+        };
+        let title = format!("Conflict: {}", conflict.title());
+        let (message, options) = if conflict.fields.is_empty() {
+            let option = match conflict.theirs {
+                Some(_) => "Keep the version edited on disk",
+                None => "Delete it like on disk",
+            };
+            let message = match conflict.theirs {
+                Some(_) => "You deleted this idea, but it was edited on disk.",
+                None => "You edited this idea, but it was deleted on disk.",
+            };
+            (message, vec![String::from(option)])
+        } else {
+            let options = conflict
+                .fields
+                .iter()
+                .map(|f| {
+                    format!(
+                        "{}: {} (disk: {})",
+                        f.name,
+                        truncate(&display_value(&f.ours), 20),
+                        truncate(&display_value(&f.theirs), 20)
+                    )
+                })
+                .collect();
+            (
+                "Changed both here and on disk. Check the fields to take from disk, Enter to apply:",
+                options,
+            )
+        };
+        self.open_modal(Modal::multi_select(
+            &title,
+            message,
+            options,
+            Action::ResolveConflict,
+        ));
+    }
+
+    /// Settles the first conflict, taking the fields at the indices in
+    /// `theirs` from disk, then asks about the next one.
+    fn resolve_conflict(&mut self, theirs: &[usize]) {
+        if self.conflicts.is_empty() {
+            return;
+        }
+        let conflict = self.conflicts.remove(0);
+        let position = self.ideas.iter().position(|i| i.id == conflict.id);
+        match (conflict.resolve(theirs), position) {
+            (Some(idea), Some(index)) => self.ideas[index] = idea,
+            (Some(idea), None) => self.ideas.push(idea),
+            (None, Some(index)) => {
+                self.ideas.remove(index);
+            }
+            (None, None) => {}
+        }
+        self.active_index = self.active_index.min(self.ideas.len().saturating_sub(1));
+        self.schedule_save();
+        self.open_conflict();
+    }
+
+    /// Marks the store as changed. The write happens on a later tick, so bursts
+    /// of edits end up as a single save.
+    fn schedule_save(&mut self) {
+        self.pending_save = Some(Instant::now());
+    }
+
+    fn set_status(&mut self, text: &str) {
+        self.status = Some(StatusMessage {
+            text: text.to_string(),
+            expires_at: Instant::now() + STATUS_DURATION,
+        });
+        self.dirty = true;
+    }
+
+    fn quit(&mut self) -> std::io::Result<()> {
+        if self.pending_save.is_some() {
+            self.save();
+        }
+        self.save_search();
         self.exit = true;
+        std::fs::write(&self.active_index_path, self.active_index.to_string())
     }
 
     fn save_edit(&mut self) {
-        let index = self.active_index;
-        if self.ideas.len() > 0 {
-            self.ideas[index].title = self.buffer[0].clone();
-            self.ideas[index].description = self.buffer[1].clone();
+        if let Some(form) = &self.form
+            && let Kind::Edit(id) = form.kind()
+            && let Some(index) = self.ideas.iter().position(|i| i.id == id)
+        {
+            // Links without a stored id reach the idea by its title.
+            let found_by_title =
+                links::resolve(&self.ideas, &self.ideas[index].title) == Some(index);
+            let old_title = std::mem::take(&mut self.ideas[index].title);
+            form.apply(&mut self.ideas[index]);
+            self.ideas[index].touch();
+            self.ideas[index].links = links::link_ids(&self.ideas, &self.ideas[index]);
+            let new_title = self.ideas[index].title.clone();
+
+            // Keep the text of links to a renamed idea in step with its title.
+            if old_title != new_title {
+                let old_title = found_by_title.then_some(old_title.as_str());
+                for idea in self.ideas.iter_mut() {
+                    if links::retarget(idea, id, old_title, id, &new_title) {
+                        idea.touch();
+                    }
+                }
+            }
+        }
+    }
+
+    fn submit_form(&mut self) {
+        let Some(kind) = self.form.as_ref().map(|f| f.kind()) else {
+            return;
+        };
+        match kind {
+            Kind::Create => {
+                let form = self.form.as_ref().unwrap();
+                let mut idea = Idea::new(form.title(), form.description());
+                form.apply(&mut idea);
+                if let Some(idea) = self.check_duplicate(idea) {
+                    self.add_new_idea(idea);
+                }
+            }
+            Kind::Edit(_) => {
+                self.save_edit();
+                self.close_form();
+                self.set_status("Idea saved");
+                self.schedule_save();
+            }
         }
     }
-    fn load_buffer(&mut self) -> bool {
-        let idea = self.ideas.get(self.active_index);
-        if let Some(dea) = idea {
-            self.buffer[0] = dea.title.clone();
-            self.buffer[1] = dea.description.clone();
-            return true;
+
+    fn open_form(&mut self, form: IdeaForm) {
+        self.form = Some(form);
+        self.mode = Mode::Form;
+    }
+
+    /// Closes the popup. An unfinished new idea is kept as a draft and restored
+    /// the next time the write popup is opened.
+    fn close_form(&mut self) {
+        if let Some(form) = self.form.take()
+            && form.kind() == Kind::Create
+            && form.has_unsaved()
+        {
+            self.draft = Some(form);
         }
-        false
+        self.mode = Mode::Read;
+    }
+
+    fn has_unsaved_form(&self) -> bool {
+        let open = self
+            .form
+            .as_ref()
+            .is_some_and(|f| f.kind() == Kind::Create && f.has_unsaved());
+        open || self.draft.is_some()
     }
 }
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, NaiveDate};
+use ratatui::widgets::{Bar, BarChart, BarGroup, Clear};
+
+use super::idea::{Idea, Status, now};
+use super::utils::{popup_rect, today};
+use super::{
+    App, Block, Borders, Color, Constraint, Direction, Frame, KeyCode, KeyEvent, Layout, Line,
+    Mode, Paragraph, Rect, Span, Style,
+};
+
+/// How many weeks of captured ideas are counted.
+pub const WEEKS: usize = 12;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Ideas captured in the week starting on `start`, a monday.
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct Week {
+    pub start: NaiveDate,
+    pub count: usize,
+}
+
+/// Figures about the whole store, worked out from the ideas' timestamps.
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct Stats {
+    pub total: usize,
+    pub statuses: BTreeMap<&'static str, usize>,
+    pub tags: BTreeMap<String, usize>,
+    /// The last `WEEKS` weeks, oldest first.
+    pub weeks: Vec<Week>,
+    /// Median days since ideas that aren't done or archived were created.
+    pub median_open_age_days: Option<f64>,
+    /// The share of ideas that are done.
+    pub completion_rate: Option<f64>,
+}
+
+impl Stats {
+    pub fn new(ideas: &[Idea], now: u64, today: NaiveDate) -> Self {
+        let mut statuses: BTreeMap<&'static str, usize> =
+            Status::ALL.iter().map(|s| (s.label(), 0)).collect();
+        let mut tags: BTreeMap<String, usize> = BTreeMap::new();
+        for idea in ideas {
+            *statuses.entry(idea.status.label()).or_default() += 1;
+            for tag in &idea.tags {
+                *tags.entry(tag.clone()).or_default() += 1;
+            }
+        }
+
+        let this_week = week_start(today);
+        let mut weeks: Vec<Week> = (0..WEEKS as u64)
+            .rev()
+            .filter_map(|n| this_week.checked_sub_days(Days::new(n * 7)))
+            .map(|start| Week { start, count: 0 })
+            .collect();
+        for idea in ideas {
+            let Some(created) = chrono::DateTime::from_timestamp(idea.created_at as i64, 0) else {
+                continue;
+            };
+            let start = week_start(created.with_timezone(&chrono::Local).date_naive());
+            if let Some(week) = weeks.iter_mut().find(|w| w.start == start) {
+                week.count += 1;
+            }
+        }
+
+        let mut ages: Vec<f64> = ideas
+            .iter()
+            .filter(|i| i.status != Status::Done && !i.archived)
+            .map(|i| now.saturating_sub(i.created_at) as f64 / SECONDS_PER_DAY as f64)
+            .collect();
+        ages.sort_by(f64::total_cmp);
+        let median_open_age_days = match ages.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(ages[n / 2]),
+            n => Some((ages[n / 2 - 1] + ages[n / 2]) / 2.0),
+        };
+
+        let done = statuses[Status::Done.label()];
+        let completion_rate = (!ideas.is_empty()).then(|| done as f64 / ideas.len() as f64);
+
+        Self {
+            total: ideas.len(),
+            statuses,
+            tags,
+            weeks,
+            median_open_age_days,
+            completion_rate,
+        }
+    }
+
+    /// The figures as lines of text, shared by the stats screen and
+    /// `ideas stats`.
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = vec![format!("{} ideas", self.total)];
+        for status in Status::ALL {
+            lines.push(format!(
+                "  {:<10} {}",
+                status.label(),
+                self.statuses[status.label()]
+            ));
+        }
+        if let Some(rate) = self.completion_rate {
+            lines.push(format!("completion rate  {:.0}%", rate * 100.0));
+        }
+        if let Some(age) = self.median_open_age_days {
+            lines.push(format!("median open age  {:.0} days", age));
+        }
+        if !self.tags.is_empty() {
+            lines.push(String::from("tags"));
+            let mut tags: Vec<(&String, &usize)> = self.tags.iter().collect();
+            tags.sort_by(|a, b| b.1.cmp(a.1));
+            for (tag, count) in tags {
+                lines.push(format!("  #{:<9} {}", tag, count));
+            }
+        }
+        lines
+    }
+}
+
+/// The monday of the week `date` is in.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+/// A screen with counts per status and tag and how many ideas were captured
+/// each week.
+pub trait Handler {
+    fn open_stats(&mut self);
+    fn handle_stats_key(&mut self, key_event: KeyEvent);
+    fn render_stats(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl Handler for App {
+    fn open_stats(&mut self) {
+        self.mode = Mode::Stats;
+    }
+
+    fn handle_stats_key(&mut self, key_event: KeyEvent) {
+        if let KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') = key_event.code {
+            self.mode = Mode::Read;
+        }
+    }
+
+    fn render_stats(&mut self, frame: &mut Frame, area: Rect) {
+        let rect = popup_rect(70, 70, (40, 13), (100, 36), area);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+        let bar_style = Style::new().fg(Color::Green);
+
+        let stats = Stats::new(&self.ideas, now(), today());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Stats ")
+            .title_bottom(" esc close ")
+            .style(passive_style);
+        let inner = block.inner(rect);
+        frame.render_widget(Clear, rect);
+        frame.render_widget(block, rect);
+
+        let [text_area, chart_area] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(30), Constraint::Min(10)])
+            .areas(inner);
+
+        let lines: Vec<Line> = stats
+            .summary()
+            .into_iter()
+            .map(|line| Line::from(Span::styled(line, text_style)))
+            .collect();
+        frame.render_widget(Paragraph::new(lines), text_area);
+
+        // Narrow bars can't fit a date under them.
+        let bar_width = (chart_area.width / WEEKS as u16)
+            .saturating_sub(1)
+            .clamp(1, 6);
+        let bars: Vec<Bar> = stats
+            .weeks
+            .iter()
+            .map(|week| {
+                let bar = Bar::default().value(week.count as u64);
+                if bar_width >= 5 {
+                    bar.label(Line::from(week.start.format("%m/%d").to_string()))
+                } else {
+                    bar
+                }
+            })
+            .collect();
+        let chart = BarChart::default()
+            .block(Block::default().title(format!("Captured per week, last {}", WEEKS)))
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .bar_style(bar_style)
+            .value_style(Style::new().fg(Color::Black).bg(Color::Green))
+            .label_style(passive_style);
+        frame.render_widget(chart, chart_area);
+    }
+}
+
+mod tests {
+    #[test]
+    fn test_stats() {
+        use crate::app::idea::{Idea, Status};
+        use crate::app::stats::Stats;
+        use chrono::NaiveDate;
+
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let day = 24 * 60 * 60;
+        let now = today.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp() as u64;
+        let mut ideas = Vec::new();
+        for (age, status, tags) in [
+            (2, Status::Inbox, vec!["rust"]),
+            (4, Status::Active, vec!["rust", "tui"]),
+            (30, Status::Done, vec![]),
+        ] {
+            let mut idea = Idea::new("", "");
+            idea.created_at = now - age * day;
+            idea.status = status;
+            idea.tags = tags.into_iter().map(String::from).collect();
+            ideas.push(idea);
+        }
+
+        let stats = Stats::new(&ideas, now, today);
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.statuses["done"], 1);
+        assert_eq!(stats.statuses["someday"], 0);
+        assert_eq!(stats.tags["rust"], 2);
+        assert_eq!(stats.median_open_age_days, Some(3.0));
+        assert_eq!(stats.completion_rate, Some(1.0 / 3.0));
+        assert_eq!(
+            stats.weeks.last().unwrap().start,
+            today - chrono::Days::new(2)
+        );
+        assert_eq!(stats.weeks.iter().map(|w| w.count).sum::<usize>(), 3);
+    }
+}
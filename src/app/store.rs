@@ -0,0 +1,197 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::crypto::{Cipher, is_encrypted};
+use super::idea::{Idea, content_id};
+use super::merge::{Conflict, merge};
+
+/// The json file the ideas are kept in. Remembers what the file looked like
+/// when it was last read or written, to notice and merge external changes.
+///
+/// Every access holds an advisory lock on a `.lock` file next to the store, so
+/// several instances can share it: writes re-read the file under the lock and
+/// merge what others saved instead of overwriting it.
+///
+/// With a cipher set the file is kept encrypted; reading an encrypted file
+/// without one fails with `PermissionDenied`, as does a wrong passphrase.
+pub struct Store {
+    path: PathBuf,
+    lock_path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+    base: Vec<Idea>,
+    cipher: Option<Cipher>,
+}
+
+impl Store {
+    pub fn new(path: PathBuf) -> Self {
+        let mut lock_path = path.clone().into_os_string();
+        lock_path.push(".lock");
+        Self {
+            path,
+            lock_path: PathBuf::from(lock_path),
+            stamp: None,
+            base: Vec::new(),
+            cipher: None,
+        }
+    }
+
+    pub fn lock(&self, exclusive: bool) -> std::io::Result<File> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.lock_path)?;
+        if exclusive {
+            file.lock()?;
+        } else {
+            file.lock_shared()?;
+        }
+        Ok(file)
+    }
+
+    fn read_stamp(&self) -> Option<(SystemTime, u64)> {
+        let metadata = std::fs::metadata(&self.path).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    /// Encrypts the store with `cipher` from the next write on, or writes it as
+    /// plain json again when `None`.
+    pub fn set_cipher(&mut self, cipher: Option<Cipher>) {
+        self.cipher = cipher;
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Reads the ideas from disk without touching the remembered state.
+    fn read(&mut self) -> std::io::Result<Vec<Idea>> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        if !is_encrypted(&bytes) {
+            return parse_ideas(&bytes);
+        }
+        match self.cipher.as_mut() {
+            Some(cipher) => parse_ideas(&cipher.decrypt(&bytes)?),
+            None => Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "the store is encrypted",
+            )),
+        }
+    }
+
+    /// Writes to a temporary file first so readers never see half a store.
+    fn write(&mut self, ideas: &[Idea]) -> std::io::Result<()> {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let mut bytes = serde_json::to_vec_pretty(ideas)?;
+        if let Some(cipher) = self.cipher.as_mut() {
+            bytes = cipher.encrypt(&bytes)?;
+        }
+        std::fs::write(&tmp_path, bytes)?;
+        std::fs::rename(&tmp_path, &self.path)
+    }
+
+    pub fn load(&mut self) -> std::io::Result<Vec<Idea>> {
+        let _lock = self.lock(false)?;
+        let ideas = self.read()?;
+        self.stamp = self.read_stamp();
+        self.base = ideas.clone();
+        Ok(ideas)
+    }
+
+    /// Saves `ours`, first merging in anything other instances saved since we
+    /// last synced. Returns what was written and the conflicting ideas, for
+    /// which our version was kept.
+    pub fn save(&mut self, ours: &[Idea]) -> std::io::Result<(Vec<Idea>, Vec<Conflict>)> {
+        let _lock = self.lock(true)?;
+        let (ideas, conflicts) = if self.read_stamp() != self.stamp {
+            let theirs = self.read()?;
+            merge(&self.base, ours, &theirs)
+        } else {
+            (ours.to_vec(), Vec::new())
+        };
+        self.write(&ideas)?;
+        self.stamp = self.read_stamp();
+        self.base = ideas.clone();
+        Ok((ideas, conflicts))
+    }
+
+    /// Whether someone else wrote the file since we last read or wrote it.
+    pub fn changed_on_disk(&self) -> bool {
+        self.read_stamp() != self.stamp
+    }
+
+    /// Reads the file again and merges it into `ours`, matching ideas by id.
+    /// Returns the merged list and the ideas both sides changed differently.
+    pub fn reload(&mut self, ours: &[Idea]) -> std::io::Result<(Vec<Idea>, Vec<Conflict>)> {
+        let _lock = self.lock(false)?;
+        let theirs = self.read()?;
+        let merged = merge(&self.base, ours, &theirs);
+        self.stamp = self.read_stamp();
+        self.base = theirs;
+        Ok(merged)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn base(&self) -> &[Idea] {
+        &self.base
+    }
+}
+
+/// Reads an idea list from a plain json file. A missing file is an empty list.
+pub fn read_ideas(path: &Path) -> std::io::Result<Vec<Idea>> {
+    match std::fs::read(path) {
+        Ok(bytes) => parse_ideas(&bytes),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parses an idea list, giving ideas without an id one derived from their
+/// contents.
+pub fn parse_ideas(bytes: &[u8]) -> std::io::Result<Vec<Idea>> {
+    let mut ideas: Vec<Idea> = serde_json::from_slice(bytes)?;
+    for idea in ideas.iter_mut().filter(|i| i.id == 0) {
+        idea.id = content_id(idea);
+    }
+    Ok(ideas)
+}
+
+mod tests {
+    #[test]
+    fn test_concurrent_saves_keep_both_additions() {
+        use crate::app::idea::Idea;
+        use crate::app::store::Store;
+
+        let dir = std::env::temp_dir().join(format!("ideas-store-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ideas.json");
+        std::fs::write(&path, "[]").unwrap();
+
+        let mut first = Store::new(path.clone());
+        let mut second = Store::new(path.clone());
+        let mut first_ideas = first.load().unwrap();
+        let mut second_ideas = second.load().unwrap();
+
+        first_ideas.push(Idea::new("first", ""));
+        first.save(&first_ideas).unwrap();
+        second_ideas.push(Idea::new("second", ""));
+        let (saved, conflicts) = second.save(&second_ideas).unwrap();
+
+        let titles: Vec<&str> = saved.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["second", "first"]);
+        assert!(conflicts.is_empty());
+        assert_eq!(Store::new(path).load().unwrap(), saved);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{Days, NaiveDate};
+use ratatui::widgets::Clear;
+
+use super::idea::{Idea, Status};
+use super::outline::Handler as OutlineHandler;
+use super::utils::{popup_rect, today};
+use super::{
+    App, Block, Borders, Color, Frame, KeyCode, KeyEvent, Line, Mode, Paragraph, Rect, Span, Style,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Group {
+    Overdue,
+    Today,
+    ThisWeek,
+    Later,
+}
+
+impl Group {
+    pub fn of(date: NaiveDate, today: NaiveDate) -> Group {
+        if date < today {
+            Group::Overdue
+        } else if date == today {
+            Group::Today
+        } else if date <= today + Days::new(7) {
+            Group::ThisWeek
+        } else {
+            Group::Later
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Group::Overdue => "Overdue",
+            Group::Today => "Today",
+            Group::ThisWeek => "This week",
+            Group::Later => "Later",
+        }
+    }
+
+    /// Whether ideas in this group need attention now.
+    pub fn is_due(self) -> bool {
+        matches!(self, Group::Overdue | Group::Today)
+    }
+}
+
+/// A due or review date of an idea.
+pub struct Entry {
+    pub index: usize,
+    pub date: NaiveDate,
+    pub kind: &'static str,
+    pub group: Group,
+}
+
+/// The due and review dates of all ideas that aren't done or archived,
+/// soonest first.
+pub fn entries(ideas: &[Idea], today: NaiveDate) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = ideas
+        .iter()
+        .enumerate()
+        .filter(|(_, idea)| idea.status != Status::Done && !idea.archived)
+        .flat_map(|(index, idea)| {
+            [(idea.due, "due"), (idea.review, "review")]
+                .into_iter()
+                .filter_map(move |(date, kind)| {
+                    date.map(|date| Entry {
+                        index,
+                        date,
+                        kind,
+                        group: Group::of(date, today),
+                    })
+                })
+        })
+        .collect();
+    entries.sort_by_key(|e| (e.date, e.index));
+    entries
+}
+
+/// Ideas grouped by when their dates come up: overdue, today, this week and
+/// later.
+pub trait Handler {
+    fn open_timeline(&mut self);
+    fn handle_timeline_key(&mut self, key_event: KeyEvent);
+    fn render_timeline(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl Handler for App {
+    fn open_timeline(&mut self) {
+        self.timeline_index = 0;
+        self.mode = Mode::Timeline;
+    }
+
+    fn handle_timeline_key(&mut self, key_event: KeyEvent) {
+        let entries = entries(&self.ideas, today());
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Read,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.timeline_index = self.timeline_index.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.timeline_index + 1 < entries.len() => {
+                self.timeline_index += 1;
+            }
+            KeyCode::Enter => {
+                if let Some(entry) = entries.get(self.timeline_index) {
+                    self.active_index = entry.index;
+                    self.reveal(entry.index);
+                    self.mode = Mode::Read;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn render_timeline(&mut self, frame: &mut Frame, area: Rect) {
+        let rect = popup_rect(70, 70, (40, 13), (100, 40), area);
+        let active_style = Style::new().fg(Color::Green);
+        let passive_style = Style::new().fg(Color::DarkGray);
+        let text_style = Style::new().fg(Color::White);
+
+        let today = today();
+        let entries = entries(&self.ideas, today);
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        let mut group = None;
+        for (i, entry) in entries.iter().enumerate() {
+            if group != Some(entry.group) {
+                if group.is_some() {
+                    lines.push(Line::from(""));
+                }
+                let style = match entry.group {
+                    Group::Overdue => Style::new().fg(Color::Red),
+                    Group::Today => Style::new().fg(Color::Yellow),
+                    _ => passive_style,
+                };
+                lines.push(Line::styled(entry.group.label(), style));
+                group = Some(entry.group);
+            }
+            let (prefix, style) = if i == self.timeline_index {
+                selected_line = lines.len();
+                ("> ", active_style)
+            } else {
+                ("  ", text_style)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(prefix, style),
+                Span::styled(format!("{}  {:<7}", entry.date, entry.kind), passive_style),
+                Span::styled(self.ideas[entry.index].title.clone(), style),
+            ]));
+        }
+        if lines.is_empty() {
+            lines.push(Line::styled(
+                "No due or review dates. Set them in the idea popup.",
+                passive_style,
+            ));
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Timeline ")
+            .title_bottom(" j/k move  enter go to idea  esc close ")
+            .style(passive_style);
+        let height = block.inner(rect).height.max(1);
+        let offset = (selected_line as u16).saturating_sub(height - 1);
+        let para = Paragraph::new(lines).block(block).scroll((offset, 0));
+        frame.render_widget(Clear, rect);
+        frame.render_widget(para, rect);
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use super::{Constraint, Direction, Layout, Rect};
 
 #[allow(dead_code)]
@@ -33,3 +35,66 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+/// Like `centered_rect`, but keeps the popup between a minimum and maximum size
+/// in cells. The popup never exceeds the area it is centered in.
+pub fn popup_rect(
+    percent_x: u16,
+    percent_y: u16,
+    min: (u16, u16),
+    max: (u16, u16),
+    r: Rect,
+) -> Rect {
+    let width = (r.width as u32 * percent_x as u32 / 100) as u16;
+    let height = (r.height as u32 * percent_y as u32 / 100) as u16;
+    let width = width.clamp(min.0, max.0).min(r.width);
+    let height = height.clamp(min.1, max.1).min(r.height);
+
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Path of a data file. They live in the checkout the binary was built in
+/// (three levels above `target/<profile>/ideas`), or in `$IDEAS_DIR` if set.
+pub fn data_path(name: &str) -> PathBuf {
+    let mut path = match std::env::var_os("IDEAS_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let mut path = std::env::current_exe().unwrap();
+            path.pop();
+            path.pop();
+            path.pop();
+            path
+        }
+    };
+    path.push(name);
+    path
+}
+
+/// Formats unix seconds as a local date and time.
+pub fn format_timestamp(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|t| t.with_timezone(&chrono::Local))
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// The local date.
+pub fn today() -> chrono::NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Shortens `text` to at most `max` chars on one line, marking cuts with "…".
+pub fn truncate(text: &str, max: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() <= max && line.len() == text.len() {
+        return line.to_string();
+    }
+    let mut short: String = line.chars().take(max.saturating_sub(1)).collect();
+    short.push('…');
+    short
+}
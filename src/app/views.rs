@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use super::modal::{Action, Handler as ModalHandler, Modal};
+use super::outline::Handler as OutlineHandler;
+use super::query::Query;
+use super::state::Handler as StateHandler;
+use super::utils::{today, truncate};
+use super::{App, Block, Borders, Color, Frame, Line, Paragraph, Rect, Style};
+
+/// A filter saved under a name, listed in the sidebar.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct SmartView {
+    pub name: String,
+    pub query: String,
+}
+
+/// Reads the saved views; a missing file means there are none.
+pub fn load_views(path: &Path) -> std::io::Result<Vec<SmartView>> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+fn save_views(path: &Path, views: &[SmartView]) -> std::io::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(views)?)
+}
+
+/// Filtering the list with a query, and the saved views that make filters
+/// quick to get back to.
+pub trait Handler {
+    fn set_filter(&mut self, text: &str);
+    fn open_filter_prompt(&mut self);
+    /// Steps through no filter and the saved views in order.
+    fn cycle_view(&mut self, step: isize);
+    fn save_view(&mut self, name: &str);
+    fn remove_view(&mut self, name: &str);
+    fn run_command(&mut self, line: &str) -> bool;
+    fn render_sidebar(&mut self, frame: &mut Frame, area: Rect);
+}
+
+impl App {
+    /// Indices of the ideas the filter lets through, along with their
+    /// parents so matches keep their place in the outline. `None` without a
+    /// filter.
+    pub fn filtered(&self) -> Option<HashSet<usize>> {
+        let (_, query) = self.filter.as_ref()?;
+        let today = today();
+        let mut shown = HashSet::new();
+        for (index, idea) in self.ideas.iter().enumerate() {
+            if !query.matches(idea, today) {
+                continue;
+            }
+            shown.insert(index);
+            let mut parent = idea.parent;
+            while let Some(id) = parent
+                && let Some(index) = self.ideas.iter().position(|i| i.id == id)
+                && shown.insert(index)
+            {
+                parent = self.ideas[index].parent;
+            }
+        }
+        Some(shown)
+    }
+
+    /// The position of the current filter among the views, 0 being none.
+    fn view_position(&self) -> Option<usize> {
+        match &self.filter {
+            None => Some(0),
+            Some((text, _)) => self
+                .views
+                .iter()
+                .position(|v| v.query == *text)
+                .map(|p| p + 1),
+        }
+    }
+
+    /// Moves the selection onto the list when the filter hid it.
+    fn select_shown(&mut self) {
+        let rows = self.outline_rows();
+        if !rows.iter().any(|(i, _)| *i == self.active_index)
+            && let Some((index, _)) = rows.first()
+        {
+            self.active_index = *index;
+        }
+    }
+}
+
+impl Handler for App {
+    fn set_filter(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            self.filter = None;
+            self.set_status("Filter cleared");
+            return;
+        }
+        match Query::parse(text) {
+            Ok(query) => {
+                self.filter = Some((text.to_string(), query));
+                self.select_shown();
+                let count = self.filtered().map_or(0, |shown| shown.len());
+                self.set_status(&format!("{} ideas shown", count));
+            }
+            Err(err) => self.set_status(&format!("Filter: {}", err)),
+        }
+    }
+
+    fn open_filter_prompt(&mut self) {
+        let current = self.filter.as_ref().map(|(text, _)| text.as_str());
+        self.open_modal(Modal::prompt(
+            "Filter",
+            "e.g. tag:rust status:active priority>=2 created:<30d \"socket\"",
+            current.unwrap_or_default(),
+            Action::Filter,
+        ));
+    }
+
+    fn cycle_view(&mut self, step: isize) {
+        let count = self.views.len() as isize + 1;
+        let position = self.view_position().unwrap_or(0) as isize;
+        let next = (position + step).rem_euclid(count) as usize;
+        match next.checked_sub(1).map(|i| self.views[i].clone()) {
+            Some(view) => {
+                self.set_filter(&view.query);
+                self.set_status(&format!("View: {}", view.name));
+            }
+            None => self.set_filter(""),
+        }
+    }
+
+    fn save_view(&mut self, name: &str) {
+        let name = name.trim();
+        let Some((query, _)) = &self.filter else {
+            self.set_status("Set a filter first, then save it as a view");
+            return;
+        };
+        if name.is_empty() {
+            self.set_status("A view needs a name");
+            return;
+        }
+        let view = SmartView {
+            name: name.to_string(),
+            query: query.clone(),
+        };
+        match self.views.iter_mut().find(|v| v.name == name) {
+            Some(existing) => *existing = view,
+            None => self.views.push(view),
+        }
+        match save_views(&self.views_path, &self.views) {
+            Ok(()) => self.set_status(&format!("Saved view {:?}", name)),
+            Err(err) => self.set_status(&format!("Couldn't save views: {}", err)),
+        }
+    }
+
+    fn remove_view(&mut self, name: &str) {
+        let before = self.views.len();
+        self.views.retain(|v| v.name != name.trim());
+        if self.views.len() == before {
+            self.set_status(&format!("No view named {:?}", name.trim()));
+            return;
+        }
+        match save_views(&self.views_path, &self.views) {
+            Ok(()) => self.set_status(&format!("Removed view {:?}", name.trim())),
+            Err(err) => self.set_status(&format!("Couldn't save views: {}", err)),
+        }
+    }
+
+    /// Runs the view commands typed in the palette. Returns false for other
+    /// commands.
+    fn run_command(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "" => {}
+            "filter" | "f" => self.set_filter(argument),
+            "save" => self.save_view(argument),
+            "unsave" => self.remove_view(argument),
+            "view" => match self.views.iter().find(|v| v.name == argument.trim()) {
+                Some(view) => {
+                    let query = view.query.clone();
+                    self.set_filter(&query);
+                }
+                None => self.set_status(&format!("No view named {:?}", argument.trim())),
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn render_sidebar(&mut self, frame: &mut Frame, area: Rect) {
+        let active_style = Style::new().fg(Color::Green);
+        let text_style = Style::new().fg(Color::White);
+        let passive_style = Style::new().fg(Color::DarkGray);
+
+        let width = area.width.saturating_sub(4) as usize;
+        let current = self.view_position();
+        let names = std::iter::once("All").chain(self.views.iter().map(|v| v.name.as_str()));
+        let lines: Vec<Line> = names
+            .enumerate()
+            .map(|(i, name)| {
+                let name = truncate(name, width);
+                if current == Some(i) {
+                    Line::styled(format!("> {}", name), active_style)
+                } else {
+                    Line::styled(format!("  {}", name), text_style)
+                }
+            })
+            .collect();
+        let block = Block::default()
+            .borders(Borders::RIGHT)
+            .title(" Views ")
+            .style(passive_style);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
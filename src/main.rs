@@ -1,12 +1,31 @@
 mod app;
 use app::App;
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+};
 
 fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(err) = app::cli::run(&args) {
+            eprintln!("ideas: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let terminal = ratatui::init();
-    let mut buffer = [String::new(), String::new()];
-    let mut app = App::new(&mut buffer);
-    let result = app.run(terminal);
+    let result = crossterm::execute!(std::io::stdout(), EnableMouseCapture, EnableBracketedPaste)
+        .and_then(|()| App::new().run(terminal));
+    // Restore the terminal first so a failure below can't leave it in raw
+    // mode. Turning the input modes off only fails when the terminal is
+    // gone, and then there's nothing left to clean up.
     ratatui::restore();
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        DisableMouseCapture,
+        DisableBracketedPaste
+    );
 
     result
 }